@@ -1,4 +1,4 @@
-use intcode::{Intcode, csv_to_vec};
+use intcode::{Intcode, loader};
 use std::collections::HashMap;
 
 struct Robot {
@@ -80,9 +80,9 @@ impl Orientation {
 
 impl Robot {
     fn new() -> Self {
-        let prog = std::fs::read_to_string("program.txt").expect("couldn't read program");
+        let prog = loader::load("program.txt").unwrap_or_else(|e| panic!("{}", e));
         Self {
-            cpu: Intcode::new(csv_to_vec(prog)),
+            cpu: Intcode::new(prog),
             orientation: Orientation::Up,
             x: 0,
             y: 0,
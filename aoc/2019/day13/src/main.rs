@@ -65,8 +65,8 @@ impl JoystickState {
 
 impl Game {
     fn new() -> Self {
-        let prog = std::fs::read_to_string("program.txt").expect("couldn't read program");
-        let cpu = Intcode::new(csv_to_vec(prog));
+        let prog = loader::load("program.txt").unwrap_or_else(|e| panic!("{}", e));
+        let cpu = Intcode::new(prog);
         Self {
             cpu,
             tiles: HashMap::new(),
@@ -79,8 +79,11 @@ impl Game {
     fn advance(&mut self, joystick: JoystickState) {
         self.cpu.input.push_back(joystick.to());
         self.cpu.run();
+        self.draw();
+    }
 
-        // update screen/score from output
+    // update screen/score from output
+    fn draw(&mut self) {
         while self.cpu.output.len() > 0 {
             assert!(self.cpu.output.len() >= 3);
             let x = self.cpu.output.pop_front().unwrap();
@@ -139,17 +142,38 @@ fn main() {
     }
     {
         let mut game = Game::new();
-        game.cpu.write_memory(0, 2);
-        while !game.done() {
-            let js = if game.paddle_x < game.ball_x {
+        game.cpu
+            .apply_patch(&Patch::Set {
+                addr: 0,
+                expected: Some(1),
+                value: 2,
+            })
+            .expect("insert coins");
+
+        // Follow the ball with the paddle, reading their positions from the draw commands.
+        let (mut ball_x, mut paddle_x, mut seen) = (0, 0, 0);
+        game.cpu.set_input_provider(move |output| {
+            let draws: Vec<i64> = output.range(seen..).copied().collect();
+            seen = output.len();
+            // x == -1 is a score update.
+            for draw in draws.chunks_exact(3).filter(|d| d[0] != -1) {
+                match Tile::from(draw[2]) {
+                    Tile::Ball => ball_x = draw[0],
+                    Tile::HorizontalPaddle => paddle_x = draw[0],
+                    _ => {}
+                }
+            }
+            let js = if paddle_x < ball_x {
                 JoystickState::Right
-            } else if game.paddle_x > game.ball_x {
+            } else if paddle_x > ball_x {
                 JoystickState::Left
             } else {
                 JoystickState::Neutral
             };
-            game.advance(js);
-            game.render();
-        }
+            Some(js.to())
+        });
+        game.cpu.run();
+        game.draw();
+        game.render();
     }
 }
@@ -60,8 +60,8 @@ struct Droid {
 
 impl Droid {
     fn new() -> Self {
-        let prog = std::fs::read_to_string("program.txt").expect("couldn't read program");
-        let cpu = Intcode::new(csv_to_vec(prog));
+        let prog = loader::load("program.txt").unwrap_or_else(|e| panic!("{}", e));
+        let cpu = Intcode::new(prog);
         Self { cpu }
     }
 
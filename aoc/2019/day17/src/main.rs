@@ -551,9 +551,9 @@ fn commit_route(cpu: &mut Intcode, route: &Route) {
 }
 
 fn main() {
-    let program = std::fs::read_to_string("program.txt").unwrap();
+    let program = loader::load("program.txt").unwrap_or_else(|e| panic!("{}", e));
     let map = {
-        let mut cpu = Intcode::new(csv_to_vec(program.clone()));
+        let mut cpu = Intcode::new(program.clone());
 
         cpu.run();
         String::from_utf8(cpu.output.iter().map(|x| *x as u8).collect::<Vec<u8>>()).unwrap()
@@ -570,8 +570,13 @@ fn main() {
     let map = Map::new(map);
     map.render();
 
-    let mut cpu = Intcode::new(csv_to_vec(program));
-    cpu.write_memory(0, 2);
+    let mut cpu = Intcode::new(program);
+    cpu.apply_patch(&Patch::Set {
+        addr: 0,
+        expected: Some(1),
+        value: 2,
+    })
+    .expect("wake the robot");
 
     println!("{}x{}", map.num_rows, map.num_cols);
 
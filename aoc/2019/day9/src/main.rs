@@ -1,7 +1,7 @@
-use intcode::{Intcode, csv_to_vec};
+use intcode::{Intcode, loader};
 
 fn main() {
-    let prog = csv_to_vec(std::fs::read_to_string("input.txt").expect("Could not read input"));
+    let prog = loader::load("input.txt").unwrap_or_else(|e| panic!("{}", e));
     let mut intcode = Intcode::new(prog);
     intcode.input.push_front(/* part */ 2);
     intcode.run();
@@ -0,0 +1,241 @@
+//! Runs many copies of one program in parallel, e.g. for parameter sweeps.
+
+use crate::Intcode;
+use crate::callstack::Fault;
+use crate::diff::Snapshot;
+use crate::patch::{PatchError, PatchSet};
+use std::fmt;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// One run of the batch's program: patches to apply, then input to feed it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Job {
+    pub input: Vec<i64>,
+    pub patches: PatchSet,
+}
+
+/// Why a job has no final state.
+#[derive(Clone, Debug, PartialEq)]
+pub enum JobError {
+    Patch(PatchError),
+    Fault(Fault),
+    /// Still running after the batch's cycle limit.
+    TooManyCycles,
+}
+
+impl From<PatchError> for JobError {
+    fn from(err: PatchError) -> Self {
+        JobError::Patch(err)
+    }
+}
+
+impl fmt::Display for JobError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            JobError::Patch(err) => write!(f, "{}", err),
+            JobError::Fault(fault) => write!(f, "{}", fault),
+            JobError::TooManyCycles => write!(f, "still running at the cycle limit"),
+        }
+    }
+}
+
+impl std::error::Error for JobError {}
+
+pub struct Batch {
+    program: Vec<i64>,
+    threads: usize,
+    max_cycles: u64,
+}
+
+impl Batch {
+    pub fn new(program: Vec<i64>) -> Self {
+        Self {
+            program,
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            max_cycles: 10_000_000,
+        }
+    }
+
+    pub fn threads(mut self, threads: usize) -> Self {
+        assert!(threads > 0);
+        self.threads = threads;
+        self
+    }
+
+    /// Upper bound on cycles per job, so a job that loops forever doesn't hang the batch.
+    pub fn max_cycles(mut self, max_cycles: u64) -> Self {
+        self.max_cycles = max_cycles;
+        self
+    }
+
+    /// Runs every job until it halts or waits for input. Results are in the same order as |jobs|.
+    pub fn run<I>(&self, jobs: I) -> Vec<Result<Snapshot, JobError>>
+    where
+        I: IntoIterator<Item = Job>,
+        I::IntoIter: Send,
+    {
+        self.scatter(jobs, |_| false)
+            .into_iter()
+            .map(|(_, result)| result)
+            .collect()
+    }
+
+    /// Finds the first job, in the order of |jobs|, whose final state satisfies |pred|. Jobs after
+    /// a match aren't started, so |jobs| can be unbounded as long as something matches.
+    pub fn find_first<I, F>(&self, jobs: I, pred: F) -> Option<(usize, Snapshot)>
+    where
+        I: IntoIterator<Item = Job>,
+        I::IntoIter: Send,
+        F: Fn(&Snapshot) -> bool + Sync,
+    {
+        self.scatter(jobs, |result| result.as_ref().is_ok_and(&pred))
+            .into_iter()
+            .find_map(|(i, result)| result.ok().filter(&pred).map(|s| (i, s)))
+    }
+
+    fn run_job(&self, job: &Job) -> Result<Snapshot, JobError> {
+        let mut intcode = Intcode::new(self.program.clone());
+        intcode.apply_patches(&job.patches)?;
+        intcode.input = job.input.iter().copied().collect();
+        match intcode.try_run_for(self.max_cycles) {
+            Ok(true) => Ok(intcode.snapshot()),
+            Ok(false) => Err(JobError::TooManyCycles),
+            Err(fault) => Err(JobError::Fault(fault)),
+        }
+    }
+
+    /// Hands jobs out to the worker threads until they run out or one satisfies |stop|, and
+    /// returns whatever finished sorted by job index. Every job before the first stop finishes.
+    fn scatter<I, F>(&self, jobs: I, stop: F) -> Vec<(usize, Result<Snapshot, JobError>)>
+    where
+        I: IntoIterator<Item = Job>,
+        I::IntoIter: Send,
+        F: Fn(&Result<Snapshot, JobError>) -> bool + Sync,
+    {
+        let jobs = Mutex::new(jobs.into_iter().enumerate());
+        let first_stop = AtomicUsize::new(usize::MAX);
+        let results = Mutex::new(vec![]);
+
+        thread::scope(|s| {
+            for _ in 0..self.threads {
+                s.spawn(|| {
+                    loop {
+                        // Jobs come out in order, so once we're past a stop so is everyone else.
+                        let Some((i, job)) = jobs.lock().unwrap().next() else {
+                            break;
+                        };
+                        if i > first_stop.load(Ordering::Relaxed) {
+                            break;
+                        }
+                        let result = self.run_job(&job);
+                        if stop(&result) {
+                            first_stop.fetch_min(i, Ordering::Relaxed);
+                        }
+                        results.lock().unwrap().push((i, result));
+                    }
+                });
+            }
+        });
+
+        let mut results = results.into_inner().unwrap();
+        results.sort_by_key(|(i, _)| *i);
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::patch::Patch;
+
+    fn set(addr: usize, value: i64) -> Patch {
+        Patch::Set {
+            addr,
+            expected: None,
+            value,
+        }
+    }
+
+    #[test]
+    fn results_in_order() {
+        // Outputs twice its input.
+        let batch = Batch::new(vec![3, 0, 102, 2, 0, 0, 4, 0, 99]).threads(4);
+        let results = batch.run((0..100).map(|i| Job {
+            input: vec![i],
+            ..Default::default()
+        }));
+        let outputs: Vec<i64> = results
+            .iter()
+            .map(|r| r.as_ref().unwrap().output[0])
+            .collect();
+        assert_eq!(outputs, (0..100).map(|i| i * 2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn find_first_noun_verb() {
+        // mem[0] = noun * verb, like a tiny day 2.
+        let batch = Batch::new(vec![1102, 0, 0, 0, 99]).threads(3);
+        let jobs = (0..).flat_map(|noun| {
+            (0..10).map(move |verb| Job {
+                patches: PatchSet {
+                    patches: vec![set(1, noun), set(2, verb)],
+                },
+                ..Default::default()
+            })
+        });
+        let (i, snapshot) = batch.find_first(jobs, |s| s.read_memory(0) == 12).unwrap();
+        assert_eq!(i, 26);
+        assert_eq!((snapshot.read_memory(1), snapshot.read_memory(2)), (2, 6));
+    }
+
+    #[test]
+    fn patch_errors() {
+        let batch = Batch::new(vec![99]);
+        let results = batch.run([Job {
+            patches: PatchSet::parse("0: 1 -> 2").unwrap(),
+            ..Default::default()
+        }]);
+        match &results[0] {
+            Err(JobError::Patch(err)) => assert_eq!(err.found, 99),
+            other => panic!("expected a patch error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn faults_and_cycle_limits() {
+        // Echoes its input. The middle jobs patch in a bad opcode and an endless loop.
+        let batch = Batch::new(vec![3, 0, 4, 0, 99]).threads(2).max_cycles(1000);
+        let jobs = [
+            Job {
+                input: vec![1],
+                ..Default::default()
+            },
+            Job {
+                patches: PatchSet {
+                    patches: vec![set(2, 42)],
+                },
+                input: vec![2],
+            },
+            Job {
+                patches: PatchSet {
+                    patches: vec![set(2, 1105), set(3, 1), set(4, 2)],
+                },
+                input: vec![3],
+            },
+            Job {
+                input: vec![4],
+                ..Default::default()
+            },
+        ];
+        let results = batch.run(jobs);
+        assert_eq!(results[0].as_ref().unwrap().output, vec![1]);
+        match &results[1] {
+            Err(JobError::Fault(fault)) => assert_eq!(fault.pc, 2),
+            other => panic!("expected a fault, got {:?}", other),
+        }
+        assert_eq!(results[2], Err(JobError::TooManyCycles));
+        assert_eq!(results[3].as_ref().unwrap().output, vec![4]);
+    }
+}
@@ -0,0 +1,233 @@
+//! Call stacks inferred from the calling convention compiled Intcode programs use.
+//!
+//! The caller writes the return address to `[rb+0]`, arguments after it, and jumps to the
+//! function. The function starts with `109 N` to move the relative base past its frame, and
+//! returns with `109 -N` followed by `2106 0,0` (jump to `[rb+0]`). So a `109 N` with N > 0 right
+//! after a taken jump pushes a frame, and moving the relative base back down pops it.
+
+use crate::{ExecutionState, Intcode};
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Frame {
+    /// Address of the function's `109 N` prologue.
+    pub function: usize,
+    pub return_address: i64,
+    /// Relative base before the prologue, where the return address lives.
+    pub base: i64,
+}
+
+/// Where the machine is, and the frames that led there, outermost first.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Backtrace {
+    pub pc: usize,
+    pub frames: Vec<Frame>,
+}
+
+impl fmt::Display for Backtrace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let function = |i: usize| match i {
+            0 => "<top>".to_string(),
+            i => format!("fn_{}", self.frames[i - 1].function),
+        };
+        let depth = self.frames.len();
+        writeln!(f, "#0 {} in {}", self.pc, function(depth))?;
+        for (n, i) in (0..depth).rev().enumerate() {
+            writeln!(
+                f,
+                "#{} {} in {}",
+                n + 1,
+                self.frames[i].return_address,
+                function(i)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FaultKind {
+    BadInstruction,
+    /// A negative address, with the memory policy set to error on those.
+    NegativeAddress(i64),
+    /// An address above the memory policy's `max_address`.
+    AddressOutOfRange(usize),
+    /// A write that would grow memory past the memory policy's `max_cells`.
+    OutOfMemory,
+    /// An add, multiply or relative base adjustment that doesn't fit in an i64.
+    Overflow,
+    /// A jump to a negative address.
+    BadJump(i64),
+}
+
+/// An instruction the machine couldn't execute.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Fault {
+    pub kind: FaultKind,
+    pub pc: usize,
+    pub instruction: i64,
+    pub backtrace: Backtrace,
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let what = match self.kind {
+            FaultKind::BadInstruction => "bad instruction".to_string(),
+            FaultKind::NegativeAddress(addr) => format!("negative address {}", addr),
+            FaultKind::AddressOutOfRange(addr) => format!("address {} out of range", addr),
+            FaultKind::OutOfMemory => "out of memory".to_string(),
+            FaultKind::Overflow => "arithmetic overflow".to_string(),
+            FaultKind::BadJump(addr) => format!("jump to {}", addr),
+        };
+        writeln!(f, "{} at {} ({})", what, self.pc, self.instruction)?;
+        write!(f, "{}", self.backtrace)
+    }
+}
+
+impl std::error::Error for Fault {}
+
+impl Intcode {
+    pub fn backtrace(&self) -> Backtrace {
+        Backtrace {
+            pc: self.pc,
+            frames: self.frames.clone(),
+        }
+    }
+
+    /// What went wrong, if the machine stopped on an instruction it couldn't execute.
+    pub fn fault(&self) -> Option<Fault> {
+        let Some(ExecutionState::Faulted(kind)) = self.execution_state else {
+            return None;
+        };
+        Some(Fault {
+            kind,
+            pc: self.pc,
+            instruction: self.read_memory(self.pc),
+            backtrace: self.backtrace(),
+        })
+    }
+
+    /// Called by `AdjustRelativeBase` after updating the relative base from |old_base|, with pc
+    /// still on the instruction. |landed| is whether the previous instruction jumped here.
+    pub(crate) fn track_relative_base(&mut self, old_base: i64, landed: bool) {
+        if self.relative_base > old_base && landed {
+            self.log_frames();
+            self.frames.push(Frame {
+                function: self.pc,
+                return_address: usize::try_from(old_base).map_or(0, |a| self.read_memory(a)),
+                base: old_base,
+            });
+        } else if self.relative_base < old_base
+            && self
+                .frames
+                .last()
+                .is_some_and(|f| f.base >= self.relative_base)
+        {
+            self.log_frames();
+            while self
+                .frames
+                .last()
+                .is_some_and(|f| f.base >= self.relative_base)
+            {
+                self.frames.pop();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // main calls f(5), f calls g(5), g outputs its argument, and they all return. main then
+    // outputs 7 and halts.
+    const PROGRAM: [i64; 43] = [
+        109, 100, // 0: main
+        21101, 13, 0, 0, // 2: [rb] = return address
+        21101, 5, 0, 1, // 6: [rb+1] = 5
+        1105, 1, 16, // 10: call f
+        104, 7,  // 13
+        99, // 15
+        109, 2, // 16: f
+        21101, 29, 0, 0, // 18
+        22101, 0, -1, 1, // 22: [rb+1] = [rb-1]
+        1105, 1, 34, // 26: call g
+        109, -2, // 29
+        2106, 0, 0, // 31: return
+        109, 1, // 34: g
+        204, 0, // 36: output [rb]
+        109, -1, // 38
+        2106, 0, 0, // 40: return
+    ];
+
+    #[test]
+    fn frames_pop_on_return() {
+        let mut intcode = Intcode::new(PROGRAM.to_vec());
+        let mut max_depth = 0;
+        while intcode.step() {
+            max_depth = max_depth.max(intcode.backtrace().frames.len());
+        }
+        assert_eq!(intcode.output, vec![5, 7]);
+        assert_eq!(max_depth, 2);
+        assert!(intcode.backtrace().frames.is_empty());
+    }
+
+    #[test]
+    fn backtrace_from_fault() {
+        let mut program = PROGRAM.to_vec();
+        program[38] = 42;
+        let mut intcode = Intcode::new(program);
+        while intcode.step() {}
+        assert!(intcode.is_faulted());
+        assert_eq!(intcode.output, vec![5]);
+
+        let fault = intcode.fault().unwrap();
+        let frames: Vec<(usize, i64)> = fault
+            .backtrace
+            .frames
+            .iter()
+            .map(|f| (f.function, f.return_address))
+            .collect();
+        assert_eq!(frames, vec![(16, 13), (34, 29)]);
+        assert_eq!(
+            fault.to_string(),
+            "bad instruction at 38 (42)\n\
+             #0 38 in fn_34\n\
+             #1 29 in fn_16\n\
+             #2 13 in <top>\n"
+        );
+    }
+
+    #[test]
+    fn no_stale_jumps() {
+        // Jumps to the 109 if [20] is set, otherwise falls through to it. Only the jump is a call.
+        let mut intcode = Intcode::new(vec![1005, 20, 3, 109, 5, 99]);
+        intcode.enable_history();
+        let start = intcode.snapshot();
+        intcode.write_memory(20, 1);
+        assert!(intcode.step());
+        let jumped = intcode.snapshot();
+        intcode.run();
+        assert_eq!(intcode.backtrace().frames.len(), 1);
+
+        assert!(intcode.run_back_to(0));
+        intcode.write_memory(20, 0);
+        intcode.run();
+        assert!(intcode.backtrace().frames.is_empty());
+
+        intcode.restore(&jumped);
+        intcode.run();
+        assert_eq!(intcode.backtrace().frames.len(), 1);
+        intcode.restore(&start);
+        intcode.run();
+        assert!(intcode.backtrace().frames.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "#1 29 in fn_16")]
+    fn run_reports_fault() {
+        let mut program = PROGRAM.to_vec();
+        program[38] = 42;
+        Intcode::new(program).run();
+    }
+}
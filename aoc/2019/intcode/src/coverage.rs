@@ -0,0 +1,228 @@
+//! Records which parts of a program a run exercised.
+//!
+//! Addresses are split into code (executed as an instruction) and data (read or written through
+//! parameters). Jumps and comparisons also count how often they went each way, so a set of test
+//! inputs can be checked for branches it never takes.
+
+use crate::disasm::{self, Line};
+use crate::{Intcode, Opcode, Operation};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Coverage {
+    executed: BTreeMap<usize, u64>,
+    /// [false/not taken, true/taken] counts for jumps and comparisons.
+    branches: BTreeMap<usize, [u64; 2]>,
+    reads: BTreeSet<usize>,
+    writes: BTreeSet<usize>,
+}
+
+impl Coverage {
+    /// How many times the instruction at |addr| was executed.
+    pub fn hits(&self, addr: usize) -> u64 {
+        self.executed.get(&addr).copied().unwrap_or(0)
+    }
+
+    /// How many times the jump or comparison at |addr| came out false and true.
+    pub fn branch(&self, addr: usize) -> Option<(u64, u64)> {
+        self.branches.get(&addr).map(|[f, t]| (*f, *t))
+    }
+
+    pub fn was_read(&self, addr: usize) -> bool {
+        self.reads.contains(&addr)
+    }
+
+    pub fn was_written(&self, addr: usize) -> bool {
+        self.writes.contains(&addr)
+    }
+
+    /// Adds in the coverage of another run, e.g. of the same program with different input.
+    pub fn merge(&mut self, other: &Coverage) {
+        for (addr, hits) in other.executed.iter() {
+            *self.executed.entry(*addr).or_default() += hits;
+        }
+        for (addr, [f, t]) in other.branches.iter() {
+            let counts = self.branches.entry(*addr).or_default();
+            counts[0] += f;
+            counts[1] += t;
+        }
+        self.reads.extend(other.reads.iter());
+        self.writes.extend(other.writes.iter());
+    }
+
+    /// Disassembles |program| using what was executed to tell code from data. Instructions that
+    /// never ran are still decoded as long as none of their words ran as something else.
+    pub fn lines(&self, program: &[i64]) -> Vec<Line> {
+        disasm::disassemble_with(program, |line| {
+            self.hits(line.addr) > 0
+                || (line.addr + 1..line.addr + line.words.len()).all(|a| self.hits(a) == 0)
+        })
+    }
+
+    /// Annotated listing of |program|. Each line starts with how often the instruction ran (`#####`
+    /// if never) or, for data, `r`/`w` for whether it was read/written. Jumps and comparisons
+    /// show how often they went each way.
+    pub fn listing(&self, program: &[i64]) -> String {
+        let mut out = String::new();
+        for line in self.lines(program) {
+            let mark = if line.is_instruction {
+                match self.hits(line.addr) {
+                    0 => "#####".to_string(),
+                    hits => hits.to_string(),
+                }
+            } else {
+                let r = if self.was_read(line.addr) { 'r' } else { '-' };
+                let w = if self.was_written(line.addr) {
+                    'w'
+                } else {
+                    '-'
+                };
+                format!("{}{}", r, w)
+            };
+            write!(out, "{:>9} {}", mark, line).unwrap();
+            if let Some((f, t)) = self.branch(line.addr) {
+                write!(out, "  ; false {} true {}", f, t).unwrap();
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// lcov tracefile for |program|, with instruction addresses standing in for line numbers.
+    pub fn lcov(&self, program: &[i64], name: &str) -> String {
+        let lines = self.lines(program);
+        let mut out = format!("TN:\nSF:{}\n", name);
+        let (mut found, mut hit) = (0, 0);
+        for line in lines.iter().filter(|l| l.is_instruction) {
+            let hits = self.hits(line.addr);
+            writeln!(out, "DA:{},{}", line.addr, hits).unwrap();
+            found += 1;
+            hit += (hits > 0) as usize;
+        }
+
+        let (mut branches_found, mut branches_hit) = (0, 0);
+        for line in lines.iter().filter(|l| l.is_instruction) {
+            let is_branch = matches!(
+                Opcode::new(line.words[0]).operation(),
+                Operation::JumpIfTrue
+                    | Operation::JumpIfFalse
+                    | Operation::LessThan
+                    | Operation::Equals
+            );
+            if !is_branch {
+                continue;
+            }
+            let counts = self.branches.get(&line.addr);
+            for outcome in 0..2 {
+                // lcov uses `-` for branches whose instruction never ran.
+                let taken = counts.map_or("-".to_string(), |c| c[outcome].to_string());
+                writeln!(out, "BRDA:{},0,{},{}", line.addr, outcome, taken).unwrap();
+                branches_found += 1;
+                branches_hit += counts.is_some_and(|c| c[outcome] > 0) as usize;
+            }
+        }
+
+        writeln!(out, "BRF:{}\nBRH:{}", branches_found, branches_hit).unwrap();
+        writeln!(out, "LF:{}\nLH:{}\nend_of_record", found, hit).unwrap();
+        out
+    }
+}
+
+impl Intcode {
+    /// Starts recording coverage from here on.
+    pub fn enable_coverage(&mut self) {
+        if self.coverage.is_none() {
+            self.coverage = Some(Coverage::default());
+        }
+    }
+
+    pub fn coverage(&self) -> Option<&Coverage> {
+        self.coverage.as_ref()
+    }
+
+    /// Stops recording coverage and returns what was recorded.
+    pub fn take_coverage(&mut self) -> Option<Coverage> {
+        self.coverage.take()
+    }
+
+    pub(crate) fn cover_execute(&mut self) {
+        if let Some(coverage) = self.coverage.as_mut() {
+            *coverage.executed.entry(self.pc).or_default() += 1;
+        }
+    }
+
+    pub(crate) fn cover_branch(&mut self, outcome: bool) {
+        if let Some(coverage) = self.coverage.as_mut() {
+            coverage.branches.entry(self.pc).or_default()[outcome as usize] += 1;
+        }
+    }
+
+    pub(crate) fn cover_read(&mut self, addr: usize) {
+        if let Some(coverage) = self.coverage.as_mut() {
+            coverage.reads.insert(addr);
+        }
+    }
+
+    pub(crate) fn cover_write(&mut self, addr: usize) {
+        if let Some(coverage) = self.coverage.as_mut() {
+            coverage.writes.insert(addr);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Day 5's larger example: outputs 999 if the input is below 8, 1000 if it's 8 and 1001 if it's
+    // above.
+    const PROGRAM: [i64; 47] = [
+        3, 21, 1008, 21, 8, 20, 1005, 20, 22, 107, 8, 21, 20, 1006, 20, 31, 1106, 0, 36, 98, 0, 0,
+        1002, 21, 125, 20, 4, 20, 1105, 1, 46, 104, 999, 1105, 1, 46, 1101, 1000, 1, 20, 4, 20,
+        1105, 1, 46, 98, 99,
+    ];
+
+    fn run(input: i64) -> Coverage {
+        let mut intcode = Intcode::new(PROGRAM.to_vec());
+        intcode.enable_coverage();
+        intcode.input.push_back(input);
+        intcode.run();
+        intcode.take_coverage().unwrap()
+    }
+
+    #[test]
+    fn single_input_misses_branches() {
+        let coverage = run(8);
+        assert_eq!(coverage.hits(0), 1);
+        // `eq [21], 8, [20]` came out true, so the `jnz [20], 22` was taken.
+        assert_eq!(coverage.branch(2), Some((0, 1)));
+        assert_eq!(coverage.branch(6), Some((0, 1)));
+        assert_eq!(coverage.branch(9), None);
+        assert!(coverage.was_written(21) && coverage.was_read(21));
+        assert!(!coverage.was_written(0));
+
+        let lcov = coverage.lcov(&PROGRAM, "day5");
+        assert!(lcov.contains("DA:9,0\n"));
+        assert!(lcov.contains("BRDA:9,0,0,-\n"));
+        assert!(lcov.ends_with("BRF:16\nBRH:3\nLF:15\nLH:7\nend_of_record\n"));
+    }
+
+    #[test]
+    fn merged_inputs_cover_every_branch() {
+        let mut coverage = run(7);
+        coverage.merge(&run(8));
+        coverage.merge(&run(9));
+        for addr in [2, 6, 9, 13] {
+            let (f, t) = coverage.branch(addr).unwrap();
+            assert!(f > 0 && t > 0, "branch at {} only went one way", addr);
+        }
+
+        let listing = coverage.listing(&PROGRAM);
+        assert!(listing.contains("        3      2: 1008,21,8,20"));
+        assert!(listing.contains("; false 2 true 1"));
+        assert!(!listing.contains("#####"));
+        // The 98s are padding that's never touched.
+        assert!(listing.contains("       --     19: 98"));
+    }
+}
@@ -0,0 +1,120 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// A peripheral mapped into an address range of an `Intcode` machine. Offsets are relative to
+/// the start of the range the device was attached at.
+pub trait Device {
+    fn read(&mut self, offset: usize) -> i64;
+    fn write(&mut self, offset: usize, val: i64);
+}
+
+// Lets the host keep a handle on a device after attaching it, e.g. to look at a framebuffer once
+// the program has drawn into it.
+impl<D: Device> Device for Rc<RefCell<D>> {
+    fn read(&mut self, offset: usize) -> i64 {
+        self.borrow_mut().read(offset)
+    }
+
+    fn write(&mut self, offset: usize, val: i64) {
+        self.borrow_mut().write(offset, val)
+    }
+}
+
+/// Row-major grid of pixels, one cell per pixel.
+pub struct Framebuffer {
+    width: usize,
+    height: usize,
+    pixels: Vec<i64>,
+}
+
+impl Framebuffer {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width * height],
+        }
+    }
+
+    /// Number of addresses the framebuffer occupies.
+    pub fn len(&self) -> usize {
+        self.pixels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pixels.is_empty()
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> i64 {
+        self.pixels[y * self.width + x]
+    }
+}
+
+impl Device for Framebuffer {
+    fn read(&mut self, offset: usize) -> i64 {
+        self.pixels.get(offset).copied().unwrap_or(0)
+    }
+
+    fn write(&mut self, offset: usize, val: i64) {
+        if let Some(pixel) = self.pixels.get_mut(offset) {
+            *pixel = val;
+        }
+    }
+}
+
+/// Counts up by one every time it's read. Writing sets the current time.
+#[derive(Default)]
+pub struct Clock {
+    ticks: i64,
+}
+
+impl Clock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Device for Clock {
+    fn read(&mut self, _offset: usize) -> i64 {
+        let now = self.ticks;
+        self.ticks += 1;
+        now
+    }
+
+    fn write(&mut self, _offset: usize, val: i64) {
+        self.ticks = val;
+    }
+}
+
+/// xorshift64* generator. Reads return a non-negative number, writes reseed.
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        // xorshift gets stuck at 0 forever.
+        Self { state: seed.max(1) }
+    }
+}
+
+impl Device for Random {
+    fn read(&mut self, _offset: usize) -> i64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        (self.state.wrapping_mul(0x2545_f491_4f6c_dd1d) >> 1) as i64
+    }
+
+    fn write(&mut self, _offset: usize, val: i64) {
+        self.state = (val as u64).max(1);
+    }
+}
@@ -0,0 +1,311 @@
+//! Comparing machine states, for figuring out where a program keeps its variables.
+
+use crate::callstack::Frame;
+use crate::{ExecutionState, Intcode};
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::fmt;
+
+/// Copy of everything that makes up a machine's state, except attached devices.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Snapshot {
+    pub pc: usize,
+    pub relative_base: i64,
+    pub memory: HashMap<usize, i64>,
+    pub input: VecDeque<i64>,
+    pub output: VecDeque<i64>,
+    pub cycles: u64,
+    execution_state: Option<ExecutionState>,
+    frames: Vec<Frame>,
+    jumped_to: Option<usize>,
+}
+
+impl Snapshot {
+    pub fn read_memory(&self, addr: usize) -> i64 {
+        *self.memory.get(&addr).unwrap_or(&0)
+    }
+}
+
+impl Intcode {
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            pc: self.pc,
+            relative_base: self.relative_base,
+            memory: self.memory.clone(),
+            input: self.input.clone(),
+            output: self.output.clone(),
+            cycles: self.cycles,
+            execution_state: self.execution_state,
+            frames: self.frames.clone(),
+            jumped_to: self.jumped_to,
+        }
+    }
+
+    /// Puts the machine back into the state of |snapshot|. The undo log is cleared since it no
+    /// longer leads back from the current state.
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        self.pc = snapshot.pc;
+        self.relative_base = snapshot.relative_base;
+        self.memory = snapshot.memory.clone();
+        self.input = snapshot.input.clone();
+        self.output = snapshot.output.clone();
+        self.cycles = snapshot.cycles;
+        self.execution_state = snapshot.execution_state;
+        self.frames = snapshot.frames.clone();
+        self.jumped_to = snapshot.jumped_to;
+        if let Some(history) = self.history.as_mut() {
+            history.clear();
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CellDiff {
+    pub addr: usize,
+    pub before: i64,
+    pub after: i64,
+}
+
+/// Differences between two snapshots. Fields are None when both sides agree.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StateDiff {
+    /// Sorted by address. Cells that were never written count as 0.
+    pub cells: Vec<CellDiff>,
+    pub pc: Option<(usize, usize)>,
+    pub relative_base: Option<(i64, i64)>,
+    pub input: Option<(Vec<i64>, Vec<i64>)>,
+    pub output: Option<(Vec<i64>, Vec<i64>)>,
+}
+
+impl StateDiff {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+fn changed<T: PartialEq>(a: T, b: T) -> Option<(T, T)> {
+    (a != b).then_some((a, b))
+}
+
+pub fn diff(a: &Snapshot, b: &Snapshot) -> StateDiff {
+    let addrs: BTreeSet<usize> = a.memory.keys().chain(b.memory.keys()).copied().collect();
+    let cells = addrs
+        .into_iter()
+        .map(|addr| CellDiff {
+            addr,
+            before: a.read_memory(addr),
+            after: b.read_memory(addr),
+        })
+        .filter(|cell| cell.before != cell.after)
+        .collect();
+
+    StateDiff {
+        cells,
+        pc: changed(a.pc, b.pc),
+        relative_base: changed(a.relative_base, b.relative_base),
+        input: changed(
+            a.input.iter().copied().collect(),
+            b.input.iter().copied().collect(),
+        ),
+        output: changed(
+            a.output.iter().copied().collect(),
+            b.output.iter().copied().collect(),
+        ),
+    }
+}
+
+impl fmt::Display for StateDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some((a, b)) = self.pc {
+            writeln!(f, "pc: {} -> {}", a, b)?;
+        }
+        if let Some((a, b)) = self.relative_base {
+            writeln!(f, "relative_base: {} -> {}", a, b)?;
+        }
+        if let Some((a, b)) = &self.input {
+            writeln!(f, "input: {:?} -> {:?}", a, b)?;
+        }
+        if let Some((a, b)) = &self.output {
+            writeln!(f, "output: {:?} -> {:?}", a, b)?;
+        }
+        for cell in self.cells.iter() {
+            writeln!(f, "mem[{}]: {} -> {}", cell.addr, cell.before, cell.after)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CellStats {
+    pub changes: usize,
+    pub min: i64,
+    pub max: i64,
+    pub last: i64,
+}
+
+/// Follows a machine across updates and keeps track of which cells keep changing. Those are the
+/// program's variables, as opposed to code and constant data.
+pub struct Watch {
+    prev: Snapshot,
+    cells: BTreeMap<usize, CellStats>,
+    last_changed: BTreeSet<usize>,
+}
+
+impl Watch {
+    pub fn new(intcode: &Intcode) -> Self {
+        Self {
+            prev: intcode.snapshot(),
+            cells: BTreeMap::new(),
+            last_changed: BTreeSet::new(),
+        }
+    }
+
+    /// Records what changed since the last update and returns it.
+    pub fn update(&mut self, intcode: &Intcode) -> StateDiff {
+        let next = intcode.snapshot();
+        let d = diff(&self.prev, &next);
+        self.last_changed.clear();
+        for cell in d.cells.iter() {
+            let stats = self.cells.entry(cell.addr).or_insert(CellStats {
+                changes: 0,
+                min: cell.before,
+                max: cell.before,
+                last: cell.before,
+            });
+            stats.changes += 1;
+            stats.min = stats.min.min(cell.after);
+            stats.max = stats.max.max(cell.after);
+            stats.last = cell.after;
+            self.last_changed.insert(cell.addr);
+        }
+        self.prev = next;
+        d
+    }
+
+    /// Cells that changed at least once, most active first.
+    pub fn variables(&self) -> Vec<(usize, &CellStats)> {
+        let mut vars: Vec<_> = self.cells.iter().map(|(addr, s)| (*addr, s)).collect();
+        vars.sort_by_key(|(addr, s)| (std::cmp::Reverse(s.changes), *addr));
+        vars
+    }
+
+    /// Table of the variables. Cells that changed in the last update are marked with a `*`.
+    pub fn render(&self) -> String {
+        let mut out = format!(
+            "  {:>6} {:>8} {:>12} {:>12} {:>12}\n",
+            "addr", "changes", "min", "max", "value"
+        );
+        for (addr, s) in self.variables() {
+            let mark = if self.last_changed.contains(&addr) {
+                '*'
+            } else {
+                ' '
+            };
+            out += &format!(
+                "{} {:>6} {:>8} {:>12} {:>12} {:>12}\n",
+                mark, addr, s.changes, s.min, s.max, s.last
+            );
+        }
+        out
+    }
+}
+
+/// Narrows down which cell holds a value the host knows about, like a score it has seen in the
+/// output. Start a scan with the current value, then keep feeding it the new value as it changes.
+pub struct Scan {
+    candidates: BTreeSet<usize>,
+}
+
+impl Scan {
+    pub fn new(intcode: &Intcode, value: i64) -> Self {
+        Self {
+            candidates: intcode
+                .memory
+                .iter()
+                .filter(|(_, v)| **v == value)
+                .map(|(addr, _)| *addr)
+                .collect(),
+        }
+    }
+
+    /// Drops every candidate that doesn't hold |value| anymore.
+    pub fn next(&mut self, intcode: &Intcode, value: i64) {
+        self.candidates
+            .retain(|addr| intcode.read_memory(*addr) == value);
+    }
+
+    pub fn candidates(&self) -> Vec<usize> {
+        self.candidates.iter().copied().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Reads n, then counts [30] up to n while adding 2 to [31] each time, outputting [31].
+    const PROGRAM: [i64; 21] = [
+        3, 32, 1001, 30, 1, 30, 1001, 31, 2, 31, 4, 31, 8, 30, 32, 33, 1006, 33, 2, 99, 0,
+    ];
+
+    #[test]
+    fn diff_runs() {
+        let mut a = Intcode::new(PROGRAM.to_vec());
+        a.input.push_back(2);
+        a.run();
+        let mut b = Intcode::new(PROGRAM.to_vec());
+        b.input.push_back(3);
+        b.run();
+
+        let d = diff(&a.snapshot(), &b.snapshot());
+        let cells: Vec<(usize, i64, i64)> = d
+            .cells
+            .iter()
+            .map(|c| (c.addr, c.before, c.after))
+            .collect();
+        assert_eq!(cells, vec![(30, 2, 3), (31, 4, 6), (32, 2, 3)]);
+        assert_eq!(d.pc, None);
+        assert_eq!(d.output, Some((vec![2, 4], vec![2, 4, 6])));
+        assert!(diff(&a.snapshot(), &a.snapshot()).is_empty());
+    }
+
+    #[test]
+    fn watch_finds_variables() {
+        let mut intcode = Intcode::new(PROGRAM.to_vec());
+        intcode.input.push_back(5);
+        let mut watch = Watch::new(&intcode);
+        while intcode.step() {
+            watch.update(&intcode);
+        }
+
+        let vars: Vec<usize> = watch.variables().iter().map(|(addr, _)| *addr).collect();
+        assert_eq!(&vars[..2], &[30, 31]);
+        assert!(watch.render().contains("10"));
+    }
+
+    #[test]
+    fn scan_narrows_down() {
+        let mut intcode = Intcode::new(PROGRAM.to_vec());
+        intcode.input.push_back(10);
+        // Run one loop iteration at a time.
+        let next_output = |intcode: &mut Intcode| {
+            let n = intcode.output.len();
+            while intcode.output.len() == n {
+                intcode.step();
+            }
+            *intcode.output.back().unwrap()
+        };
+
+        let value = next_output(&mut intcode);
+        let mut scan = Scan::new(&intcode, value);
+        assert_eq!(scan.candidates(), vec![8, 18, 31]);
+        for _ in 0..2 {
+            let value = next_output(&mut intcode);
+            scan.next(&intcode, value);
+        }
+        assert_eq!(scan.candidates(), vec![31]);
+
+        intcode.restore(&Intcode::new(PROGRAM.to_vec()).snapshot());
+        assert_eq!(intcode.pc(), 0);
+        assert!(intcode.output.is_empty());
+    }
+}
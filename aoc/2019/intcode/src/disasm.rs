@@ -0,0 +1,162 @@
+//! Turns program images back into something readable.
+//!
+//! Parameters print as `[a]` for position mode, `a` for immediate mode and `[rb+a]` for relative
+//! mode. Words that don't decode as an instruction print as `data`.
+
+use crate::{Intcode, Mode, Opcode, Operation};
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Line {
+    pub addr: usize,
+    /// The instruction's words, or the single data word.
+    pub words: Vec<i64>,
+    pub is_instruction: bool,
+    pub text: String,
+}
+
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let words: Vec<String> = self.words.iter().map(|w| w.to_string()).collect();
+        write!(f, "{:>6}: {:<28} {}", self.addr, words.join(","), self.text)
+    }
+}
+
+fn mnemonic(op: Operation) -> &'static str {
+    match op {
+        Operation::Add => "add",
+        Operation::Mul => "mul",
+        Operation::Input => "in",
+        Operation::Output => "out",
+        Operation::JumpIfTrue => "jnz",
+        Operation::JumpIfFalse => "jz",
+        Operation::LessThan => "lt",
+        Operation::Equals => "eq",
+        Operation::AdjustRelativeBase => "arb",
+        Operation::Halt => "halt",
+    }
+}
+
+fn param(mode: Mode, val: i64) -> String {
+    match mode {
+        Mode::Position => format!("[{}]", val),
+        Mode::Immediate => val.to_string(),
+        Mode::Relative if val < 0 => format!("[rb{}]", val),
+        Mode::Relative => format!("[rb+{}]", val),
+    }
+}
+
+/// Decodes the instruction at |addr|, or None if the word there isn't a valid instruction or the
+/// program ends before its parameters do.
+pub fn decode(program: &[i64], addr: usize) -> Option<Line> {
+    let opcode = Opcode::new(*program.get(addr)?);
+    if !opcode.is_valid() {
+        return None;
+    }
+    let op = opcode.operation();
+    let words = program.get(addr..addr + 1 + op.num_params())?.to_vec();
+    let params: Vec<String> = (1..=op.num_params())
+        .map(|n| param(opcode.mode(n), words[n]))
+        .collect();
+    Some(Line {
+        addr,
+        words,
+        is_instruction: true,
+        text: format!("{} {}", mnemonic(op), params.join(", "))
+            .trim_end()
+            .to_string(),
+    })
+}
+
+pub fn data(program: &[i64], addr: usize) -> Line {
+    let word = program.get(addr).copied().unwrap_or(0);
+    Line {
+        addr,
+        words: vec![word],
+        is_instruction: false,
+        text: format!("data {}", word),
+    }
+}
+
+/// Decodes the whole program front to back. Data mixed in with code will be decoded as
+/// instructions if it happens to look like some.
+pub fn disassemble(program: &[i64]) -> Vec<Line> {
+    disassemble_with(program, |_| true)
+}
+
+/// Like `disassemble`, but |is_code| decides whether each word that decodes as an instruction
+/// really is one, or should be shown as data.
+pub fn disassemble_with(program: &[i64], is_code: impl Fn(&Line) -> bool) -> Vec<Line> {
+    let mut lines = vec![];
+    let mut addr = 0;
+    while addr < program.len() {
+        let line = match decode(program, addr) {
+            Some(line) if is_code(&line) => line,
+            _ => data(program, addr),
+        };
+        addr += line.words.len();
+        lines.push(line);
+    }
+    lines
+}
+
+pub fn listing(program: &[i64]) -> String {
+    disassemble(program)
+        .iter()
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
+
+impl Intcode {
+    /// Decodes the instruction at pc, as it is in memory right now.
+    pub fn instruction(&self) -> Line {
+        let words: Vec<i64> = (0..4).map(|i| self.read_memory(self.pc + i)).collect();
+        let mut line = decode(&words, 0).unwrap_or_else(|| data(&words, 0));
+        line.addr = self.pc;
+        line
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_modes() {
+        let program = [1002, 4, 3, 4, 33];
+        assert_eq!(decode(&program, 0).unwrap().text, "mul [4], 3, [4]");
+        assert_eq!(decode(&program, 4), None);
+        assert_eq!(
+            decode(&[21101, -1, 2, -3], 0).unwrap().text,
+            "add -1, 2, [rb-3]"
+        );
+        // Runs off the end.
+        assert_eq!(decode(&[1, 0, 0], 0), None);
+    }
+
+    #[test]
+    fn disassemble_program() {
+        let lines = disassemble(&[3, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8]);
+        let text: Vec<(usize, &str)> = lines.iter().map(|l| (l.addr, l.text.as_str())).collect();
+        assert_eq!(
+            text,
+            vec![
+                (0, "in [9]"),
+                (2, "eq [9], [10], [9]"),
+                (6, "out [9]"),
+                (8, "halt"),
+                (9, "data -1"),
+                (10, "data 8"),
+            ]
+        );
+        assert_eq!(
+            lines[1].to_string(),
+            "     2: 8,9,10,9                     eq [9], [10], [9]"
+        );
+
+        let mut intcode = Intcode::new(vec![3, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8]);
+        intcode.input.push_back(8);
+        intcode.step();
+        assert_eq!(intcode.instruction(), lines[1]);
+    }
+}
@@ -0,0 +1,286 @@
+use crate::callstack::Frame;
+use crate::{ExecutionState, Intcode};
+use std::collections::VecDeque;
+
+/// How many instructions `enable_history` keeps by default.
+pub(crate) const DEFAULT_HISTORY_LIMIT: usize = 1_000_000;
+
+/// Everything an instruction changed, so it can be taken back. Device reads and writes have side
+/// effects on the host and are not rewound.
+pub(crate) struct UndoEntry {
+    pc: usize,
+    relative_base: i64,
+    cycles: u64,
+    execution_state: Option<ExecutionState>,
+    jumped_to: Option<usize>,
+    writes: Vec<(usize, Option<i64>)>,
+    input: Option<ConsumedInput>,
+    /// Length of the output queue before the instruction output something.
+    output: Option<usize>,
+    /// Call stack before the instruction, if it changed.
+    frames: Option<Vec<Frame>>,
+}
+
+pub(crate) struct ConsumedInput {
+    pub(crate) value: i64,
+    /// Position in the replay session before the input was taken, or None if it came from
+    /// `Intcode.input`.
+    pub(crate) replay: Option<(usize, Option<usize>)>,
+    pub(crate) recorded: bool,
+}
+
+impl Intcode {
+    /// Starts keeping an undo log of every instruction executed from now on, so the machine can
+    /// be rewound with `step_back`. Only the last million instructions are kept unless
+    /// `set_history_limit` says otherwise.
+    pub fn enable_history(&mut self) {
+        if self.history.is_none() {
+            self.history = Some(VecDeque::new());
+        }
+    }
+
+    /// Caps how many instructions the undo log keeps, dropping the oldest past that. None means
+    /// unbounded.
+    pub fn set_history_limit(&mut self, limit: Option<usize>) {
+        assert_ne!(limit, Some(0));
+        self.history_limit = limit;
+        self.trim_history();
+    }
+
+    fn trim_history(&mut self) {
+        if let (Some(history), Some(limit)) = (self.history.as_mut(), self.history_limit) {
+            while history.len() > limit {
+                history.pop_front();
+            }
+        }
+    }
+
+    pub fn disable_history(&mut self) {
+        self.history = None;
+    }
+
+    /// Number of instructions that can currently be undone.
+    pub fn history_len(&self) -> usize {
+        self.history.as_ref().map_or(0, |h| h.len())
+    }
+
+    /// Undoes the last executed instruction. Returns false if there's nothing left to undo.
+    pub fn step_back(&mut self) -> bool {
+        let Some(entry) = self.history.as_mut().and_then(|h| h.pop_back()) else {
+            return false;
+        };
+
+        for (addr, old) in entry.writes.into_iter().rev() {
+            match old {
+                Some(val) => self.memory.insert(addr, val),
+                None => self.memory.remove(&addr),
+            };
+        }
+
+        if let Some(input) = entry.input {
+            match (input.replay, self.replay.as_mut()) {
+                (Some((next, diverged_at)), Some(replay)) => {
+                    replay.next = next;
+                    replay.diverged_at = diverged_at;
+                }
+                _ => self.input.push_front(input.value),
+            }
+            if input.recorded
+                && let Some(recorder) = self.recorder.as_mut()
+            {
+                recorder.session.inputs.pop();
+            }
+        }
+
+        // Anything the host already drained stays gone.
+        if let Some(len) = entry.output {
+            self.output.truncate(len);
+        }
+
+        if let Some(frames) = entry.frames {
+            self.frames = frames;
+        }
+
+        self.pc = entry.pc;
+        self.relative_base = entry.relative_base;
+        self.cycles = entry.cycles;
+        self.execution_state = entry.execution_state;
+        self.jumped_to = entry.jumped_to;
+        true
+    }
+
+    /// Steps back until pc is at |addr|. Returns false if the history ran out first.
+    pub fn run_back_to(&mut self, addr: usize) -> bool {
+        while self.step_back() {
+            if self.pc == addr {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Steps back until just before the most recent instruction that wrote to |addr|, so that
+    /// pc points at the instruction that produced the current value.
+    pub fn rewind_to_write(&mut self, addr: usize) -> bool {
+        loop {
+            let wrote = match self.history.as_ref().and_then(|h| h.back()) {
+                Some(entry) => entry.writes.iter().any(|(a, _)| *a == addr),
+                None => return false,
+            };
+            self.step_back();
+            if wrote {
+                return true;
+            }
+        }
+    }
+
+    pub(crate) fn begin_undo(&mut self) {
+        if self.history.is_some() {
+            self.undo = Some(UndoEntry {
+                pc: self.pc,
+                relative_base: self.relative_base,
+                cycles: self.cycles,
+                execution_state: self.execution_state,
+                jumped_to: self.jumped_to,
+                writes: vec![],
+                input: None,
+                output: None,
+                frames: None,
+            });
+        }
+    }
+
+    pub(crate) fn end_undo(&mut self) {
+        let Some(entry) = self.undo.take() else {
+            return;
+        };
+        // An input instruction that found nothing to read didn't execute.
+        if (self.cycles != entry.cycles || self.is_halted() || self.is_faulted())
+            && let Some(history) = self.history.as_mut()
+        {
+            history.push_back(entry);
+            self.trim_history();
+        }
+    }
+
+    pub(crate) fn log_write(&mut self, addr: usize) {
+        if let Some(entry) = self.undo.as_mut() {
+            entry.writes.push((addr, self.memory.get(&addr).copied()));
+        }
+    }
+
+    pub(crate) fn log_input(&mut self, input: ConsumedInput) {
+        if let Some(entry) = self.undo.as_mut() {
+            entry.input = Some(input);
+        }
+    }
+
+    pub(crate) fn log_frames(&mut self) {
+        if let Some(entry) = self.undo.as_mut()
+            && entry.frames.is_none()
+        {
+            entry.frames = Some(self.frames.clone());
+        }
+    }
+
+    /// Called just before an output is pushed.
+    pub(crate) fn log_output(&mut self) {
+        if let Some(entry) = self.undo.as_mut() {
+            entry.output = Some(self.output.len());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Intcode;
+
+    #[test]
+    fn step_back_restores_state() {
+        let program = vec![3, 0, 4, 0, 1001, 0, 5, 0, 4, 0, 99];
+        let mut intcode = Intcode::new(program.clone());
+        intcode.enable_history();
+        intcode.input.push_back(10);
+        intcode.run();
+        assert!(intcode.is_halted());
+        assert_eq!(intcode.output, vec![10, 15]);
+        assert_eq!(intcode.history_len(), 5);
+
+        assert!(intcode.run_back_to(0));
+        assert!(!intcode.is_halted());
+        assert_eq!(intcode.pc(), 0);
+        assert_eq!(intcode.cycles(), 0);
+        assert_eq!(intcode.input, vec![10]);
+        assert!(intcode.output.is_empty());
+        for (addr, val) in program.iter().enumerate() {
+            assert_eq!(intcode.read_memory(addr), *val);
+        }
+        assert!(!intcode.step_back());
+
+        // Going forward again gives the same result.
+        intcode.run();
+        assert_eq!(intcode.output, vec![10, 15]);
+    }
+
+    #[test]
+    fn step_back_after_drain() {
+        // Outputs 5 twice.
+        let mut intcode = Intcode::new(vec![104, 5, 104, 5, 99]);
+        intcode.enable_history();
+        assert!(intcode.step());
+        assert_eq!(intcode.output.pop_front(), Some(5));
+        assert!(intcode.step());
+        assert_eq!(intcode.output, vec![5]);
+
+        // Only the second 5 is still there to take back.
+        assert!(intcode.step_back());
+        assert!(intcode.output.is_empty());
+        assert!(intcode.step_back());
+        assert!(intcode.output.is_empty());
+        intcode.run();
+        assert_eq!(intcode.output, vec![5, 5]);
+    }
+
+    #[test]
+    fn history_limit() {
+        // Counts [20] up forever.
+        let mut intcode = Intcode::new(vec![1001, 20, 1, 20, 1105, 1, 0]);
+        intcode.enable_history();
+        intcode.set_history_limit(Some(10));
+        for _ in 0..100 {
+            intcode.step();
+        }
+        assert_eq!(intcode.history_len(), 10);
+        while intcode.step_back() {}
+        assert_eq!(intcode.read_memory(20), 45);
+
+        intcode.set_history_limit(None);
+        for _ in 0..100 {
+            intcode.step();
+        }
+        assert_eq!(intcode.history_len(), 100);
+        intcode.set_history_limit(Some(3));
+        assert_eq!(intcode.history_len(), 3);
+    }
+
+    #[test]
+    fn rewind_to_write() {
+        // Count [20] down from 3 to 0, then write 7 to [21].
+        let program = vec![
+            1101, 3, 0, 20, 1001, 20, -1, 20, 1005, 20, 4, 1101, 7, 0, 21, 99,
+        ];
+        let mut intcode = Intcode::new(program);
+        intcode.enable_history();
+        intcode.run();
+        assert_eq!(intcode.read_memory(20), 0);
+
+        assert!(intcode.rewind_to_write(20));
+        assert_eq!(intcode.pc(), 4);
+        assert_eq!(intcode.read_memory(20), 1);
+        assert!(intcode.rewind_to_write(20));
+        assert_eq!(intcode.read_memory(20), 2);
+
+        assert!(!intcode.rewind_to_write(21));
+        assert_eq!(intcode.history_len(), 0);
+    }
+}
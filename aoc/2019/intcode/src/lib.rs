@@ -1,23 +1,83 @@
 use std::collections::{HashMap, VecDeque};
+use std::ops::Range;
+
+pub mod batch;
+pub mod callstack;
+pub mod coverage;
+pub mod device;
+pub mod diff;
+pub mod disasm;
+mod history;
+pub mod loader;
+pub mod memory;
+pub mod patch;
+pub mod session;
+pub mod symbolic;
+pub mod vectors;
+
+use callstack::{Fault, FaultKind, Frame};
+use coverage::Coverage;
+pub use device::Device;
+use history::{ConsumedInput, DEFAULT_HISTORY_LIMIT, UndoEntry};
+pub use memory::MemoryPolicy;
+use patch::Freeze;
+pub use patch::{Patch, PatchSet};
+use session::Recorder;
+pub use session::Session;
 
 macro_rules! debug_println {
-    ($($arg:tt)*) => (#[cfg(debug_assertions)] println!($($arg)*));
+    ($($arg:tt)*) => (#[cfg(feature = "debug-trace")] eprintln!($($arg)*));
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 enum ExecutionState {
     WaitForInput,
     Halted,
+    /// Stopped before an output instruction because the output queue is at capacity.
+    OutputFull,
+    /// Stopped right after an output instruction, in yield-on-output mode.
+    Yielded,
+    /// Stopped on an instruction that can't be executed.
+    Faulted(FaultKind),
 }
 
+/// Called for input when the queue runs dry, with the output produced so far.
+type InputProvider = Box<dyn FnMut(&VecDeque<i64>) -> Option<i64>>;
+
 pub struct Intcode {
     pc: usize,
     memory: HashMap<usize, i64>,
     relative_base: i64,
+    memory_policy: MemoryPolicy,
 
     pub input: VecDeque<i64>,
     pub output: VecDeque<i64>,
+    input_provider: Option<InputProvider>,
+    output_capacity: Option<usize>,
+    yield_on_output: bool,
     execution_state: Option<ExecutionState>,
+    devices: Vec<(Range<usize>, Box<dyn Device>)>,
+
+    cycles: u64,
+    recorder: Option<Recorder>,
+    replay: Option<Replay>,
+
+    history: Option<VecDeque<UndoEntry>>,
+    history_limit: Option<usize>,
+    undo: Option<UndoEntry>,
+
+    freezes: Vec<Freeze>,
+
+    frames: Vec<Frame>,
+    jumped_to: Option<usize>,
+
+    coverage: Option<Coverage>,
+}
+
+struct Replay {
+    session: Session,
+    next: usize,
+    diverged_at: Option<usize>,
 }
 
 impl Intcode {
@@ -30,10 +90,104 @@ impl Intcode {
             pc: 0,
             memory,
             relative_base: 0,
+            memory_policy: MemoryPolicy::default(),
             input: VecDeque::new(),
             output: VecDeque::new(),
+            input_provider: None,
+            output_capacity: None,
+            yield_on_output: false,
             execution_state: None,
+            devices: vec![],
+            cycles: 0,
+            recorder: None,
+            replay: None,
+            history: None,
+            history_limit: Some(DEFAULT_HISTORY_LIMIT),
+            undo: None,
+            freezes: vec![],
+            frames: vec![],
+            jumped_to: None,
+            coverage: None,
+        }
+    }
+
+    pub fn pc(&self) -> usize {
+        self.pc
+    }
+
+    pub fn relative_base(&self) -> i64 {
+        self.relative_base
+    }
+
+    /// Number of instructions executed so far.
+    pub fn cycles(&self) -> u64 {
+        self.cycles
+    }
+
+    /// Starts recording every value consumed by an input instruction. With |timing|, each entry
+    /// also gets the wall-clock time since recording started.
+    pub fn start_recording(&mut self, timing: bool) {
+        self.recorder = Some(Recorder::new(timing));
+    }
+
+    pub fn stop_recording(&mut self) -> Option<Session> {
+        self.recorder.take().map(|r| r.session)
+    }
+
+    /// Feeds the inputs of |session| to the program ahead of anything in `input`. Once the
+    /// session runs out, input comes from the queue again.
+    pub fn replay(&mut self, session: Session) {
+        self.replay = Some(Replay {
+            session,
+            next: 0,
+            diverged_at: None,
+        });
+    }
+
+    /// Index of the first replayed input that was consumed at a different cycle than when it was
+    /// recorded, i.e. where the program stopped following the recorded run.
+    pub fn replay_diverged_at(&self) -> Option<usize> {
+        self.replay.as_ref().and_then(|r| r.diverged_at)
+    }
+
+    /// Registers |provider| to be asked for input whenever the program wants some and `input`
+    /// is empty. It sees everything in `output` so far. Returning None makes the machine wait for
+    /// input like it would without a provider.
+    pub fn set_input_provider<F>(&mut self, provider: F)
+    where
+        F: FnMut(&VecDeque<i64>) -> Option<i64> + 'static,
+    {
+        self.input_provider = Some(Box::new(provider));
+    }
+
+    pub fn clear_input_provider(&mut self) {
+        self.input_provider = None;
+    }
+
+    fn next_input(&mut self) -> Option<i64> {
+        let mut replay_pos = None;
+        let replayed = self.replay.as_mut().and_then(|replay| {
+            let recorded = replay.session.inputs.get(replay.next)?;
+            replay_pos = Some((replay.next, replay.diverged_at));
+            if recorded.cycle != self.cycles && replay.diverged_at.is_none() {
+                replay.diverged_at = Some(replay.next);
+            }
+            replay.next += 1;
+            Some(recorded.value)
+        });
+
+        let input = replayed
+            .or_else(|| self.input.pop_front())
+            .or_else(|| self.input_provider.as_mut()?(&self.output))?;
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.record(input, self.cycles);
         }
+        self.log_input(ConsumedInput {
+            value: input,
+            replay: replay_pos,
+            recorded: self.recorder.is_some(),
+        });
+        Some(input)
     }
 
     pub fn read_memory(&self, addr: usize) -> i64 {
@@ -44,49 +198,100 @@ impl Intcode {
         self.memory.insert(addr, val);
     }
 
+    /// Maps |device| over |range|. Loads and stores the program makes through its parameters go
+    /// to the device instead of memory. `read_memory`/`write_memory` always see plain memory.
+    pub fn attach_device<D: Device + 'static>(&mut self, range: Range<usize>, device: D) {
+        assert!(
+            self.devices
+                .iter()
+                .all(|(r, _)| range.end <= r.start || r.end <= range.start),
+            "device range {:?} overlaps an attached device",
+            range
+        );
+        self.devices.push((range, Box::new(device)));
+    }
+
+    fn load(&mut self, addr: usize) -> i64 {
+        match self.devices.iter_mut().find(|(r, _)| r.contains(&addr)) {
+            Some((range, device)) => device.read(addr - range.start),
+            None => {
+                self.cover_read(addr);
+                self.read_memory(addr)
+            }
+        }
+    }
+
+    fn store(&mut self, addr: usize, val: i64) {
+        match self.devices.iter_mut().find(|(r, _)| r.contains(&addr)) {
+            Some((range, device)) => device.write(addr - range.start, val),
+            None => {
+                self.log_write(addr);
+                self.cover_write(addr);
+                self.write_memory(addr, val);
+            }
+        }
+    }
+
     pub fn is_halted(&self) -> bool {
-        self.execution_state
-            .as_ref()
-            .map_or(false, |state| match state {
-                ExecutionState::Halted => true,
-                _ => false,
-            })
+        matches!(self.execution_state, Some(ExecutionState::Halted))
     }
 
     pub fn is_waiting(&self) -> bool {
-        self.execution_state
-            .as_ref()
-            .map_or(false, |state| match state {
-                ExecutionState::WaitForInput => true,
-                _ => false,
-            })
+        matches!(self.execution_state, Some(ExecutionState::WaitForInput))
+    }
+
+    pub fn is_faulted(&self) -> bool {
+        matches!(self.execution_state, Some(ExecutionState::Faulted(_)))
+    }
+
+    pub fn is_output_full(&self) -> bool {
+        matches!(self.execution_state, Some(ExecutionState::OutputFull))
+    }
+
+    pub fn has_yielded(&self) -> bool {
+        matches!(self.execution_state, Some(ExecutionState::Yielded))
+    }
+
+    /// Limits how many values can sit in |output| before the machine stops to let the host catch
+    /// up. None (the default) means unbounded.
+    pub fn set_output_capacity(&mut self, capacity: Option<usize>) {
+        assert_ne!(capacity, Some(0));
+        self.output_capacity = capacity;
+    }
+
+    /// Makes the machine stop after every output instruction, so the host sees values one at a
+    /// time.
+    pub fn set_yield_on_output(&mut self, yield_on_output: bool) {
+        self.yield_on_output = yield_on_output;
     }
 
-    fn inp(&self, offset: usize, opcode: &Opcode) -> i64 {
+    fn inp(&mut self, offset: usize, opcode: &Opcode) -> Result<i64, FaultKind> {
         let param = self.read_memory(self.pc + offset);
-        match opcode.mode(offset) {
+        Ok(match opcode.mode(offset) {
             Mode::Immediate => {
                 debug_println!("got immediate {}", param);
                 param
             }
             Mode::Position => {
-                debug_println!(
-                    "got position: addr {} val {}",
-                    param,
-                    self.read_memory(param as usize)
-                );
-                self.read_memory(param as usize)
+                let val = self.resolve(param)?.map_or(0, |addr| self.load(addr));
+                debug_println!("got position: addr {} val {}", param, val);
+                val
             }
             Mode::Relative => {
-                let addr = self.relative_base + param;
-                let val = self.read_memory(addr as usize);
+                let addr = self
+                    .relative_base
+                    .checked_add(param)
+                    .ok_or(FaultKind::Overflow)?;
+                let val = self.resolve(addr)?.map_or(0, |addr| self.load(addr));
                 debug_println!("got relative: addr {} val {}", param, val);
                 val
             }
-        }
+        })
     }
 
-    fn outp(&mut self, offset: usize, val: i64, opcode: &Opcode) {
+    /// Address the parameter at |offset| writes to, or None if the memory policy drops the
+    /// write.
+    fn dst(&self, offset: usize, opcode: &Opcode) -> Result<Option<usize>, FaultKind> {
         // Note: outp treated differently because it's basically writing to
         // memory, but all other ones are reading.
         let dst_addr = match opcode.mode(offset) {
@@ -97,109 +302,211 @@ impl Intcode {
             }
             Mode::Relative => {
                 debug_println!("\trelative output, base = {}", self.relative_base);
-                self.read_memory(self.pc + offset) + self.relative_base
+                self.read_memory(self.pc + offset)
+                    .checked_add(self.relative_base)
+                    .ok_or(FaultKind::Overflow)?
             }
         };
+        let dst_addr = self.resolve(dst_addr)?;
+        if let Some(addr) = dst_addr {
+            self.check_grow(addr)?;
+        }
+        Ok(dst_addr)
+    }
 
-        debug_println!("\tmem[{}] = {}", dst_addr, val);
-        self.write_memory(dst_addr as usize, val);
+    fn outp(&mut self, offset: usize, val: i64, opcode: &Opcode) -> Result<(), FaultKind> {
+        if let Some(dst_addr) = self.dst(offset, opcode)? {
+            debug_println!("\tmem[{}] = {}", dst_addr, val);
+            self.store(dst_addr, val);
+        }
+        Ok(())
     }
 
-    /// Runs the program until it halts (executes opcode 99).
+    /// Runs the program until it halts (executes opcode 99), needs input, can't output because
+    /// the output queue is full, or has just output something in yield-on-output mode.
+    /// Panics with a backtrace if it hits an instruction it can't execute.
     pub fn run(&mut self) {
+        self.try_run().unwrap_or_else(|fault| panic!("{}", fault));
+    }
+
+    /// Like `run`, but returns faults instead of panicking.
+    pub fn try_run(&mut self) -> Result<(), Fault> {
         assert!(!self.is_halted());
-        loop {
-            // debug_println!("pc = [{}], mem = [{:?}]", self.pc, self.memory);
-            let opcode = Opcode::new(self.read_memory(self.pc as usize));
-            match opcode.operation() {
-                Operation::Add => {
-                    let p1 = self.inp(1, &opcode);
-                    let p2 = self.inp(2, &opcode);
-                    debug_println!("add: {} + {}", p1, p2);
-                    self.outp(3, p1 + p2, &opcode);
-                    self.pc += 4;
-                }
-                Operation::Mul => {
-                    let p1 = self.inp(1, &opcode);
-                    let p2 = self.inp(2, &opcode);
-                    debug_println!("mul: {} * {}", p1, p2);
-                    self.outp(3, p1 * p2, &opcode);
-                    self.pc += 4;
-                }
-                Operation::Input => {
-                    if self.input.is_empty() {
-                        debug_println!("Waiting for next input");
-                        self.execution_state.replace(ExecutionState::WaitForInput);
-                        return;
-                    }
-
-                    let input = self.input.pop_front().unwrap();
-                    debug_println!("input: {}", input);
-                    self.outp(1, input, &opcode);
-                    self.pc += 2;
-                }
-                Operation::Output => {
-                    let p1 = self.inp(1, &opcode);
-                    debug_println!("output: {}", p1);
-                    self.output.push_back(p1);
-                    self.pc += 2;
-                }
-                Operation::JumpIfTrue => {
-                    let cond = self.inp(1, &opcode);
-                    let jmp_addr = self.inp(2, &opcode);
-                    debug_println!("jump if true: cond {} target {}", cond, jmp_addr);
-                    if cond != 0 {
-                        self.pc = jmp_addr as usize;
-                    } else {
-                        self.pc += 3;
-                    }
-                }
-                Operation::JumpIfFalse => {
-                    let cond = self.inp(1, &opcode);
-                    let jmp_addr = self.inp(2, &opcode);
-                    debug_println!("jump if false: cond {} target {}", cond, jmp_addr);
-                    if cond == 0 {
-                        self.pc = jmp_addr as usize;
-                    } else {
-                        self.pc += 3;
-                    }
+        while self.step() {}
+        self.fault().map_or(Ok(()), Err)
+    }
+
+    /// Like `try_run`, but gives up after |max_cycles| more cycles. Returns false if it gave up
+    /// while the program could still have kept going.
+    pub fn try_run_for(&mut self, max_cycles: u64) -> Result<bool, Fault> {
+        assert!(!self.is_halted());
+        let limit = self.cycles.saturating_add(max_cycles);
+        let mut running = true;
+        while running && self.cycles < limit {
+            running = self.step();
+        }
+        self.fault().map_or(Ok(!running), Err)
+    }
+
+    /// Executes a single instruction. Returns false if the machine shouldn't keep going, for any
+    /// of the reasons `run` stops.
+    pub fn step(&mut self) -> bool {
+        if self.is_halted() || self.is_faulted() {
+            return false;
+        }
+        self.begin_undo();
+        self.execution_state = None;
+        // Cleared every step so only the instruction a jump lands on sees it.
+        let landed = self.jumped_to.take() == Some(self.pc);
+        let cycles = self.cycles;
+        let running = self.execute(landed);
+        if self.cycles != cycles {
+            self.apply_freezes();
+        } else if landed {
+            // Didn't execute, e.g. waiting for input, so it still has to see the jump next time.
+            self.jumped_to = Some(self.pc);
+        }
+        self.end_undo();
+        running
+    }
+
+    fn execute(&mut self, landed: bool) -> bool {
+        // debug_println!("pc = [{}], mem = [{:?}]", self.pc, self.memory);
+        let opcode = Opcode::new(self.read_memory(self.pc));
+        let result = if opcode.is_valid() {
+            self.cover_execute();
+            self.try_execute(opcode, landed)
+        } else {
+            debug_println!("bad instruction {}", opcode.orig);
+            Err(FaultKind::BadInstruction)
+        };
+        result.unwrap_or_else(|kind| {
+            self.execution_state.replace(ExecutionState::Faulted(kind));
+            false
+        })
+    }
+
+    /// Executes |opcode|, the instruction at pc, which |landed| says the last instruction jumped
+    /// to. Faults are returned before anything is written.
+    fn try_execute(&mut self, opcode: Opcode, landed: bool) -> Result<bool, FaultKind> {
+        match opcode.operation() {
+            Operation::Add => {
+                let p1 = self.inp(1, &opcode)?;
+                let p2 = self.inp(2, &opcode)?;
+                debug_println!("add: {} + {}", p1, p2);
+                let sum = p1.checked_add(p2).ok_or(FaultKind::Overflow)?;
+                self.outp(3, sum, &opcode)?;
+                self.pc += 4;
+            }
+            Operation::Mul => {
+                let p1 = self.inp(1, &opcode)?;
+                let p2 = self.inp(2, &opcode)?;
+                debug_println!("mul: {} * {}", p1, p2);
+                let product = p1.checked_mul(p2).ok_or(FaultKind::Overflow)?;
+                self.outp(3, product, &opcode)?;
+                self.pc += 4;
+            }
+            Operation::Input => {
+                // Check the destination first so a bad one doesn't eat the input.
+                let dst = self.dst(1, &opcode)?;
+                let Some(input) = self.next_input() else {
+                    debug_println!("Waiting for next input");
+                    self.execution_state.replace(ExecutionState::WaitForInput);
+                    return Ok(false);
+                };
+
+                debug_println!("input: {}", input);
+                if let Some(addr) = dst {
+                    self.store(addr, input);
                 }
-                Operation::LessThan => {
-                    let p1 = self.inp(1, &opcode);
-                    let p2 = self.inp(2, &opcode);
-                    debug_println!("lt: {} < {}", p1, p2);
-                    self.outp(3, if p1 < p2 { 1 } else { 0 }, &opcode);
-                    self.pc += 4;
+                self.pc += 2;
+            }
+            Operation::Output => {
+                if self
+                    .output_capacity
+                    .is_some_and(|cap| self.output.len() >= cap)
+                {
+                    debug_println!("Waiting for room in the output");
+                    self.execution_state.replace(ExecutionState::OutputFull);
+                    return Ok(false);
                 }
-                Operation::Equals => {
-                    let p1 = self.inp(1, &opcode);
-                    let p2 = self.inp(2, &opcode);
-                    debug_println!("eq: {} == {}", p1, p2);
-                    self.outp(3, if p1 == p2 { 1 } else { 0 }, &opcode);
-                    self.pc += 4;
+
+                let p1 = self.inp(1, &opcode)?;
+                debug_println!("output: {}", p1);
+                self.log_output();
+                self.output.push_back(p1);
+                self.pc += 2;
+                if self.yield_on_output {
+                    self.execution_state.replace(ExecutionState::Yielded);
+                    self.cycles += 1;
+                    return Ok(false);
                 }
-                Operation::AdjustRelativeBase => {
-                    let p1 = self.inp(1, &opcode);
-                    debug_println!("adj_rel_base: {} + {}", self.relative_base, p1);
-                    self.relative_base += p1;
-                    self.pc += 2;
+            }
+            Operation::JumpIfTrue => {
+                let cond = self.inp(1, &opcode)?;
+                let jmp_addr = self.inp(2, &opcode)?;
+                debug_println!("jump if true: cond {} target {}", cond, jmp_addr);
+                self.cover_branch(cond != 0);
+                if cond != 0 {
+                    self.pc =
+                        usize::try_from(jmp_addr).map_err(|_| FaultKind::BadJump(jmp_addr))?;
+                    self.jumped_to = Some(self.pc);
+                } else {
+                    self.pc += 3;
                 }
-                Operation::Halt => {
-                    debug_println!("halt");
-                    self.execution_state.replace(ExecutionState::Halted);
-                    return;
+            }
+            Operation::JumpIfFalse => {
+                let cond = self.inp(1, &opcode)?;
+                let jmp_addr = self.inp(2, &opcode)?;
+                debug_println!("jump if false: cond {} target {}", cond, jmp_addr);
+                self.cover_branch(cond == 0);
+                if cond == 0 {
+                    self.pc =
+                        usize::try_from(jmp_addr).map_err(|_| FaultKind::BadJump(jmp_addr))?;
+                    self.jumped_to = Some(self.pc);
+                } else {
+                    self.pc += 3;
                 }
             }
+            Operation::LessThan => {
+                let p1 = self.inp(1, &opcode)?;
+                let p2 = self.inp(2, &opcode)?;
+                debug_println!("lt: {} < {}", p1, p2);
+                self.cover_branch(p1 < p2);
+                self.outp(3, if p1 < p2 { 1 } else { 0 }, &opcode)?;
+                self.pc += 4;
+            }
+            Operation::Equals => {
+                let p1 = self.inp(1, &opcode)?;
+                let p2 = self.inp(2, &opcode)?;
+                debug_println!("eq: {} == {}", p1, p2);
+                self.cover_branch(p1 == p2);
+                self.outp(3, if p1 == p2 { 1 } else { 0 }, &opcode)?;
+                self.pc += 4;
+            }
+            Operation::AdjustRelativeBase => {
+                let p1 = self.inp(1, &opcode)?;
+                debug_println!("adj_rel_base: {} + {}", self.relative_base, p1);
+                let old_base = self.relative_base;
+                self.relative_base = old_base.checked_add(p1).ok_or(FaultKind::Overflow)?;
+                self.track_relative_base(old_base, landed);
+                self.pc += 2;
+            }
+            Operation::Halt => {
+                debug_println!("halt");
+                self.execution_state.replace(ExecutionState::Halted);
+                return Ok(false);
+            }
         }
+        self.cycles += 1;
+        Ok(true)
     }
 }
 
+/// Parses a text program, panicking on anything that isn't one. See `loader` for the accepted
+/// syntax and for non-panicking versions.
 pub fn csv_to_vec(input: String) -> Vec<i64> {
-    input
-        .trim()
-        .split(",")
-        .map(|s| s.parse::<i64>().expect(&format!("{}", s)))
-        .collect()
+    loader::parse_text(&input).unwrap_or_else(|e| panic!("{}", e))
 }
 
 #[derive(PartialEq, Debug)]
@@ -223,7 +530,7 @@ enum Mode {
     Relative,
 }
 
-#[derive(PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 struct Opcode {
     orig: i64,
 }
@@ -234,7 +541,12 @@ impl Opcode {
     }
 
     fn operation(&self) -> Operation {
-        match self.orig % 100 {
+        self.try_operation()
+            .unwrap_or_else(|| unreachable!("{}", self.orig % 100))
+    }
+
+    fn try_operation(&self) -> Option<Operation> {
+        Some(match self.orig % 100 {
             1 => Operation::Add,
             2 => Operation::Mul,
             3 => Operation::Input,
@@ -245,18 +557,55 @@ impl Opcode {
             8 => Operation::Equals,
             9 => Operation::AdjustRelativeBase,
             99 => Operation::Halt,
-            _ => unreachable!("{}", self.orig % 100),
-        }
+            _ => return None,
+        })
     }
 
     fn mode(&self, n: usize) -> Mode {
+        self.try_mode(n).unwrap_or_else(|| unreachable!())
+    }
+
+    fn try_mode(&self, n: usize) -> Option<Mode> {
         assert!(n >= 1);
-        let shifted = self.orig / (10 as i64).pow(1 + n as u32);
+        let shifted = self.orig / 10_i64.pow(1 + n as u32);
         match shifted % 10 {
-            0 => Mode::Position,
-            1 => Mode::Immediate,
-            2 => Mode::Relative,
-            _ => unreachable!(),
+            0 => Some(Mode::Position),
+            1 => Some(Mode::Immediate),
+            2 => Some(Mode::Relative),
+            _ => None,
+        }
+    }
+
+    /// Whether the instruction can be executed, i.e. it has a known operation and every
+    /// parameter has a mode that makes sense for it.
+    fn is_valid(&self) -> bool {
+        let Some(op) = self.try_operation() else {
+            return false;
+        };
+        (1..=op.num_params()).all(|n| match self.try_mode(n) {
+            Some(Mode::Immediate) => op.dst_param() != Some(n),
+            Some(_) => true,
+            None => false,
+        })
+    }
+}
+
+impl Operation {
+    fn num_params(&self) -> usize {
+        match self {
+            Operation::Add | Operation::Mul | Operation::LessThan | Operation::Equals => 3,
+            Operation::JumpIfTrue | Operation::JumpIfFalse => 2,
+            Operation::Input | Operation::Output | Operation::AdjustRelativeBase => 1,
+            Operation::Halt => 0,
+        }
+    }
+
+    /// The parameter the operation writes its result to, if any.
+    fn dst_param(&self) -> Option<usize> {
+        match self {
+            Operation::Add | Operation::Mul | Operation::LessThan | Operation::Equals => Some(3),
+            Operation::Input => Some(1),
+            _ => None,
         }
     }
 }
@@ -360,6 +709,68 @@ mod tests {
         assert_eq!(intcode.output.pop_front().unwrap(), 1219070632396864);
     }
 
+    #[test]
+    fn device_read_write() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        // Copy the clock into the first pixel twice, then write 7 to the last one.
+        let program = vec![1, 1000, 100, 2000, 1, 1000, 100, 2000, 1101, 3, 4, 2003, 99];
+        let fb = Rc::new(RefCell::new(device::Framebuffer::new(2, 2)));
+        let mut intcode = Intcode::new(program);
+        intcode.attach_device(1000..1001, device::Clock::new());
+        intcode.attach_device(2000..2004, fb.clone());
+        intcode.run();
+
+        assert_eq!(fb.borrow().pixel(0, 0), 1);
+        assert_eq!(fb.borrow().pixel(1, 1), 7);
+        // Writes went to the device, not memory.
+        assert_eq!(intcode.read_memory(2000), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn device_overlap() {
+        let mut intcode = Intcode::new(vec![99]);
+        intcode.attach_device(10..20, device::Clock::new());
+        intcode.attach_device(15..16, device::Random::new(1));
+    }
+
+    #[test]
+    fn record_and_replay() {
+        // Sum inputs until a 0 comes in, then output the total.
+        let program = vec![3, 100, 1, 100, 101, 101, 1005, 100, 0, 4, 101, 99];
+        let mut intcode = Intcode::new(program.clone());
+        intcode.start_recording(false);
+        for i in [5, 6, 7, 0] {
+            intcode.input.push_back(i);
+            intcode.run();
+        }
+        let session = intcode.stop_recording().unwrap();
+        assert_eq!(session.values().collect::<Vec<_>>(), vec![5, 6, 7, 0]);
+
+        let path = std::env::temp_dir().join("intcode_record_and_replay.session");
+        session.save(&path).unwrap();
+        let session = Session::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let mut replayed = Intcode::new(program.clone());
+        replayed.replay(session.clone());
+        replayed.run();
+        assert!(replayed.is_halted());
+        assert_eq!(replayed.output.pop_front(), Some(18));
+        assert_eq!(replayed.replay_diverged_at(), None);
+
+        // Same loop behind an extra instruction, so every input gets read one cycle later.
+        let mut shifted = Intcode::new(vec![
+            1101, 0, 0, 200, 3, 100, 1, 100, 101, 101, 1005, 100, 4, 4, 101, 99,
+        ]);
+        shifted.replay(session);
+        shifted.run();
+        assert_eq!(shifted.output.pop_front(), Some(18));
+        assert_eq!(shifted.replay_diverged_at(), Some(0));
+    }
+
     #[test]
     fn bignum2() {
         let program = vec![104, 1125899906842624, 99];
@@ -367,4 +778,72 @@ mod tests {
         intcode.run();
         assert_eq!(intcode.output.pop_front().unwrap(), 1125899906842624);
     }
+
+    // Outputs 0, 1, 2, ... forever.
+    const COUNTER: [i64; 10] = [4, 9, 1001, 9, 1, 9, 1105, 1, 0, 0];
+
+    #[test]
+    fn output_capacity() {
+        let mut intcode = Intcode::new(COUNTER.to_vec());
+        intcode.set_output_capacity(Some(3));
+        intcode.run();
+        assert!(intcode.is_output_full());
+        assert_eq!(intcode.output, vec![0, 1, 2]);
+
+        // Making room lets it carry on where it left off.
+        intcode.output.pop_front();
+        intcode.run();
+        assert!(intcode.is_output_full());
+        assert_eq!(intcode.output, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn yield_on_output() {
+        let mut intcode = Intcode::new(COUNTER.to_vec());
+        intcode.set_yield_on_output(true);
+        for i in 0..5 {
+            intcode.run();
+            assert!(intcode.has_yielded());
+            assert_eq!(intcode.output.pop_front(), Some(i));
+        }
+        assert!(intcode.output.is_empty());
+    }
+
+    #[test]
+    fn pipeline_back_pressure() {
+        // The consumer outputs the running sum of its input.
+        let mut producer = Intcode::new(COUNTER.to_vec());
+        producer.set_output_capacity(Some(1));
+        let mut consumer = Intcode::new(vec![3, 11, 1, 11, 12, 12, 4, 12, 1105, 1, 0, 0, 0]);
+        for _ in 0..10 {
+            producer.run();
+            assert!(producer.is_output_full());
+            consumer.input.extend(producer.output.drain(..));
+            consumer.run();
+            assert!(consumer.is_waiting());
+        }
+        assert_eq!(consumer.output.back(), Some(&45));
+    }
+
+    #[test]
+    fn input_provider() {
+        // Reads two numbers and outputs their sum, forever.
+        let program = vec![3, 13, 3, 14, 1, 13, 14, 15, 4, 15, 1105, 1, 0, 0, 0, 0];
+        let mut intcode = Intcode::new(program);
+        intcode.input.push_back(100);
+        // Keep feeding it the last output, until it gets big.
+        intcode.set_input_provider(|output| match output.back() {
+            Some(&last) if last > 1000 => None,
+            Some(&last) => Some(last),
+            None => Some(1),
+        });
+        intcode.run();
+        assert!(intcode.is_waiting());
+        assert_eq!(intcode.output, vec![101, 202, 404, 808, 1616]);
+
+        intcode.clear_input_provider();
+        intcode.input.extend([1, 2]);
+        intcode.run();
+        assert_eq!(intcode.output.back(), Some(&3));
+    }
 }
@@ -0,0 +1,234 @@
+//! Loads Intcode programs from disk.
+//!
+//! Text programs are integers separated by commas and/or whitespace, so both the usual one-line
+//! puzzle input and one-instruction-per-line listings work. `#` and `;` start a comment that runs
+//! to the end of the line. Binary programs (`.bin`) are little-endian i64 words. Either can be
+//! gzipped, which is detected from the file contents.
+
+use flate2::read::GzDecoder;
+use std::fmt;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    Text,
+    Binary,
+}
+
+#[derive(Debug)]
+pub enum LoadErrorKind {
+    Io(std::io::Error),
+    /// Token that isn't an integer.
+    BadToken(String),
+    /// Binary program whose length isn't a whole number of words.
+    Truncated(usize),
+    NotUtf8,
+}
+
+#[derive(Debug)]
+pub struct LoadError {
+    pub kind: LoadErrorKind,
+    pub path: Option<PathBuf>,
+    /// 1-based position of the problem, for text programs.
+    pub line: usize,
+    pub column: usize,
+}
+
+impl LoadError {
+    fn new(kind: LoadErrorKind) -> Self {
+        Self {
+            kind,
+            path: None,
+            line: 0,
+            column: 0,
+        }
+    }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(path) = &self.path {
+            write!(f, "{}:", path.display())?;
+        }
+        if self.line > 0 {
+            write!(f, "{}:{}:", self.line, self.column)?;
+        }
+        if self.path.is_some() || self.line > 0 {
+            write!(f, " ")?;
+        }
+        match &self.kind {
+            LoadErrorKind::Io(e) => write!(f, "{}", e),
+            LoadErrorKind::BadToken(token) => write!(f, "expected an integer, got {:?}", token),
+            LoadErrorKind::Truncated(len) => {
+                write!(f, "binary program is {} bytes, not a multiple of 8", len)
+            }
+            LoadErrorKind::NotUtf8 => write!(f, "text program is not valid UTF-8"),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            LoadErrorKind::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Loads the program at |path|. `.bin` files (or `.bin.gz`) are binary, anything else is text.
+pub fn load<P: AsRef<Path>>(path: P) -> Result<Vec<i64>, LoadError> {
+    let path = path.as_ref();
+    let mut name = path.file_name().unwrap_or_default().to_string_lossy();
+    if let Some(stripped) = name.strip_suffix(".gz") {
+        name = stripped.to_string().into();
+    }
+    let format = if name.ends_with(".bin") {
+        Format::Binary
+    } else {
+        Format::Text
+    };
+    load_with_format(path, format)
+}
+
+pub fn load_with_format<P: AsRef<Path>>(path: P, format: Format) -> Result<Vec<i64>, LoadError> {
+    let path = path.as_ref();
+    std::fs::read(path)
+        .map_err(|e| LoadError::new(LoadErrorKind::Io(e)))
+        .and_then(|bytes| parse(&bytes, format))
+        .map_err(|mut e| {
+            e.path = Some(path.to_path_buf());
+            e
+        })
+}
+
+/// Parses a program image, decompressing it first if it's gzipped.
+pub fn parse(bytes: &[u8], format: Format) -> Result<Vec<i64>, LoadError> {
+    if bytes.starts_with(&GZIP_MAGIC) {
+        let mut decompressed = vec![];
+        GzDecoder::new(bytes)
+            .read_to_end(&mut decompressed)
+            .map_err(|e| LoadError::new(LoadErrorKind::Io(e)))?;
+        return parse(&decompressed, format);
+    }
+
+    match format {
+        Format::Text => {
+            let text =
+                std::str::from_utf8(bytes).map_err(|_| LoadError::new(LoadErrorKind::NotUtf8))?;
+            parse_text(text)
+        }
+        Format::Binary => parse_binary(bytes),
+    }
+}
+
+pub fn parse_text(text: &str) -> Result<Vec<i64>, LoadError> {
+    let mut program = vec![];
+    for (lineno, line) in text.lines().enumerate() {
+        let code = match line.find(['#', ';']) {
+            Some(comment) => &line[..comment],
+            None => line,
+        };
+
+        let mut start = None;
+        // Walk the line with a trailing separator so the last token gets flushed too.
+        for (i, c) in code.char_indices().chain([(code.len(), ',')]) {
+            let is_separator = c == ',' || c.is_whitespace();
+            match (start, is_separator) {
+                (None, false) => start = Some(i),
+                (Some(s), true) => {
+                    let token = &code[s..i];
+                    let word = token.parse().map_err(|_| LoadError {
+                        kind: LoadErrorKind::BadToken(token.to_string()),
+                        path: None,
+                        line: lineno + 1,
+                        column: code[..s].chars().count() + 1,
+                    })?;
+                    program.push(word);
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(program)
+}
+
+pub fn parse_binary(bytes: &[u8]) -> Result<Vec<i64>, LoadError> {
+    if !bytes.len().is_multiple_of(8) {
+        return Err(LoadError::new(LoadErrorKind::Truncated(bytes.len())));
+    }
+    Ok(bytes
+        .chunks_exact(8)
+        .map(|word| i64::from_le_bytes(word.try_into().unwrap()))
+        .collect())
+}
+
+pub fn to_binary(program: &[i64]) -> Vec<u8> {
+    program.iter().flat_map(|word| word.to_le_bytes()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+
+    #[test]
+    fn text() {
+        assert_eq!(parse_text("1,2,3\n").unwrap(), vec![1, 2, 3]);
+        assert_eq!(
+            parse_text("# add\n1, 0, 0, 0 ; mem[0] *= 2\n\n99,\n").unwrap(),
+            vec![1, 0, 0, 0, 99]
+        );
+        assert_eq!(parse_text("").unwrap(), vec![]);
+    }
+
+    #[test]
+    fn text_error_position() {
+        let err = parse_text("1,2\n3, 4x,5").unwrap_err();
+        assert!(matches!(&err.kind, LoadErrorKind::BadToken(t) if t == "4x"));
+        assert_eq!((err.line, err.column), (2, 4));
+        assert_eq!(err.to_string(), "2:4: expected an integer, got \"4x\"");
+    }
+
+    #[test]
+    fn binary() {
+        let program = vec![1102, -34915192, 34915192, 7, 4, 7, 99, 0];
+        assert_eq!(parse_binary(&to_binary(&program)).unwrap(), program);
+        assert!(matches!(
+            parse_binary(&[0; 9]).unwrap_err().kind,
+            LoadErrorKind::Truncated(9)
+        ));
+    }
+
+    #[test]
+    fn gzip() {
+        let program = vec![104, 1125899906842624, 99];
+        for (format, bytes) in [
+            (Format::Text, b"104,1125899906842624,99\n".to_vec()),
+            (Format::Binary, to_binary(&program)),
+        ] {
+            let mut encoder = GzEncoder::new(vec![], Compression::default());
+            encoder.write_all(&bytes).unwrap();
+            let compressed = encoder.finish().unwrap();
+            assert_eq!(parse(&compressed, format).unwrap(), program);
+        }
+    }
+
+    #[test]
+    fn load_from_file() {
+        let path = std::env::temp_dir().join("intcode_loader_test.bin");
+        std::fs::write(&path, to_binary(&[3, 0, 4, 0, 99])).unwrap();
+        assert_eq!(load(&path).unwrap(), vec![3, 0, 4, 0, 99]);
+        std::fs::remove_file(&path).unwrap();
+
+        let err = load(&path).unwrap_err();
+        assert!(matches!(err.kind, LoadErrorKind::Io(_)));
+        assert!(err.to_string().starts_with(&path.display().to_string()));
+    }
+}
@@ -0,0 +1,218 @@
+//! Command line for poking at Intcode programs without writing a day crate for them.
+
+use intcode::{Intcode, PatchSet, disasm, loader};
+use std::io::{self, BufRead, Write};
+use std::process::ExitCode;
+use std::time::Instant;
+
+const USAGE: &str = "\
+usage: intcode <command> <program> [options]
+
+commands:
+  run      feed integers from stdin to the program, print its outputs one per line
+  ascii    like run, but lines of stdin are sent as ASCII and printable output as text
+  disasm   print a listing of the program
+  trace    like run, but print every instruction to stderr before executing it
+  bench    run the program with all of stdin as input and report its speed
+
+options:
+  --patch <file>       apply a patch file before running (repeatable)
+  --iterations <n>     how many times bench runs the program (default 10)";
+
+#[derive(Clone, Copy)]
+enum Command {
+    Run,
+    Ascii,
+    Disasm,
+    Trace,
+    Bench,
+}
+
+struct Args {
+    command: Command,
+    program: String,
+    patches: Vec<String>,
+    iterations: usize,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let command = match args.next().as_deref() {
+        Some("run") => Command::Run,
+        Some("ascii") => Command::Ascii,
+        Some("disasm") => Command::Disasm,
+        Some("trace") => Command::Trace,
+        Some("bench") => Command::Bench,
+        Some(other) => return Err(format!("unknown command {:?}", other)),
+        None => return Err("missing command".to_string()),
+    };
+
+    let mut program = None;
+    let mut patches = vec![];
+    let mut iterations = 10;
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--patch" => patches.push(value()?),
+            "--iterations" => {
+                iterations = value()?
+                    .parse()
+                    .map_err(|_| "--iterations needs a number".to_string())?
+            }
+            _ if arg.starts_with("--") => return Err(format!("unknown option {}", arg)),
+            _ if program.is_none() => program = Some(arg),
+            _ => return Err(format!("unexpected argument {:?}", arg)),
+        }
+    }
+
+    Ok(Args {
+        command,
+        program: program.ok_or("missing program")?,
+        patches,
+        iterations,
+    })
+}
+
+fn load(args: &Args) -> Result<Intcode, String> {
+    let program = loader::load(&args.program).map_err(|e| e.to_string())?;
+    let mut intcode = Intcode::new(program);
+    for path in args.patches.iter() {
+        let patches = PatchSet::load(path)?;
+        intcode
+            .apply_patches(&patches)
+            .map_err(|e| format!("{}: {}", path, e))?;
+    }
+    Ok(intcode)
+}
+
+/// Runs |intcode|, printing output and reading a line of input from stdin whenever it needs
+/// some, until it halts.
+fn interact(mut intcode: Intcode, ascii: bool, trace: bool) -> Result<(), String> {
+    let mut lines = io::stdin().lock().lines();
+    let mut stdout = io::stdout().lock();
+    loop {
+        if trace {
+            loop {
+                let line = intcode.instruction();
+                let (cycles, rb) = (intcode.cycles(), intcode.relative_base());
+                let running = intcode.step();
+                // Waiting for input doesn't execute anything, so it'd show up twice.
+                if intcode.cycles() != cycles || intcode.is_halted() || intcode.is_faulted() {
+                    eprintln!("rb={:<6} {}", rb, line);
+                }
+                if !running {
+                    break;
+                }
+            }
+        } else {
+            while intcode.step() {}
+        }
+        if let Some(fault) = intcode.fault() {
+            return Err(fault.to_string());
+        }
+
+        for val in intcode.output.drain(..) {
+            match u8::try_from(val) {
+                Ok(c) if ascii && c.is_ascii() => write!(stdout, "{}", c as char),
+                _ => writeln!(stdout, "{}", val),
+            }
+            .map_err(|e| e.to_string())?;
+        }
+        stdout.flush().map_err(|e| e.to_string())?;
+
+        if intcode.is_halted() {
+            return Ok(());
+        }
+        let Some(line) = lines.next() else {
+            return Err("program is waiting for input, but stdin is closed".to_string());
+        };
+        let line = line.map_err(|e| e.to_string())?;
+        if ascii {
+            intcode.input.extend(line.bytes().map(i64::from));
+            intcode.input.push_back('\n' as i64);
+        } else {
+            intcode
+                .input
+                .extend(loader::parse_text(&line).map_err(|e| e.to_string())?);
+        }
+    }
+}
+
+fn bench(args: &Args) -> Result<(), String> {
+    let input = io::read_to_string(io::stdin()).map_err(|e| e.to_string())?;
+    let input = loader::parse_text(&input).map_err(|e| e.to_string())?;
+
+    let mut cycles = 0;
+    let start = Instant::now();
+    for _ in 0..args.iterations {
+        let mut intcode = load(args)?;
+        intcode.input = input.iter().copied().collect();
+        intcode.try_run().map_err(|fault| fault.to_string())?;
+        cycles += intcode.cycles();
+    }
+    let elapsed = start.elapsed();
+
+    println!(
+        "{} runs, {} instructions each, {:.3?} per run, {:.1} M instructions/s",
+        args.iterations,
+        cycles / args.iterations.max(1) as u64,
+        elapsed / args.iterations.max(1) as u32,
+        cycles as f64 / elapsed.as_secs_f64() / 1e6
+    );
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let result = parse_args(std::env::args().skip(1))
+        .map_err(|e| format!("{}\n\n{}", e, USAGE))
+        .and_then(|args| match args.command {
+            Command::Run => interact(load(&args)?, false, false),
+            Command::Ascii => interact(load(&args)?, true, false),
+            Command::Trace => interact(load(&args)?, false, true),
+            Command::Disasm => {
+                let mut program = loader::load(&args.program).map_err(|e| e.to_string())?;
+                for path in args.patches.iter() {
+                    PatchSet::load(path)?
+                        .apply(&mut program)
+                        .map_err(|e| format!("{}: {}", path, e))?;
+                }
+                print!("{}", disasm::listing(&program));
+                Ok(())
+            }
+            Command::Bench => bench(&args),
+        });
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<Args, String> {
+        parse_args(args.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn args() {
+        let args = parse("bench prog.txt --patch a --iterations 3 --patch b").unwrap();
+        assert!(matches!(args.command, Command::Bench));
+        assert_eq!(args.program, "prog.txt");
+        assert_eq!(args.patches, vec!["a", "b"]);
+        assert_eq!(args.iterations, 3);
+
+        assert_eq!(parse("").err().unwrap(), "missing command");
+        assert_eq!(parse("run").err().unwrap(), "missing program");
+        assert_eq!(
+            parse("run p --patch").err().unwrap(),
+            "--patch needs a value"
+        );
+        assert!(parse("run p q").is_err());
+        assert!(parse("frob p").is_err());
+    }
+}
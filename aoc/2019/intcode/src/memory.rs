@@ -0,0 +1,207 @@
+//! Limits on the memory a program can use, for running programs we don't trust.
+//!
+//! Only accesses the program makes through its parameters are checked. `read_memory` and
+//! `write_memory` are the host's and can go anywhere.
+
+use crate::Intcode;
+use crate::callstack::FaultKind;
+
+/// What to do with a negative address, e.g. a relative parameter that points below 0.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum NegativeAddress {
+    /// Fault the machine.
+    Error,
+    /// Count down from the top of the address space: `max_address` if there is one, otherwise
+    /// wherever casting to usize lands.
+    #[default]
+    Wrap,
+    /// Ignore the access. Reads see 0 and writes are dropped.
+    Reject,
+}
+
+/// The default policy has no limits, which is how the machine always behaved.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MemoryPolicy {
+    /// Highest address the program can touch. Anything above faults the machine.
+    pub max_address: Option<usize>,
+    /// How many cells memory can grow to. Writing a new cell past that faults the machine.
+    pub max_cells: Option<usize>,
+    pub negative: NegativeAddress,
+}
+
+impl Intcode {
+    pub fn set_memory_policy(&mut self, policy: MemoryPolicy) {
+        self.memory_policy = policy;
+    }
+
+    pub fn memory_policy(&self) -> MemoryPolicy {
+        self.memory_policy
+    }
+
+    /// Turns an address computed by the program into a memory address, or None if the access
+    /// should be ignored.
+    pub(crate) fn resolve(&self, addr: i64) -> Result<Option<usize>, FaultKind> {
+        let policy = &self.memory_policy;
+        let addr = match usize::try_from(addr) {
+            Ok(addr) => addr,
+            Err(_) => match policy.negative {
+                NegativeAddress::Error => return Err(FaultKind::NegativeAddress(addr)),
+                NegativeAddress::Reject => return Ok(None),
+                // A usize::MAX limit covers the whole address space, which the cast already
+                // wraps around.
+                NegativeAddress::Wrap => match policy.max_address {
+                    Some(max) if max < usize::MAX => {
+                        (addr as i128).rem_euclid(max as i128 + 1) as usize
+                    }
+                    _ => addr as usize,
+                },
+            },
+        };
+        match policy.max_address {
+            Some(max) if addr > max => Err(FaultKind::AddressOutOfRange(addr)),
+            _ => Ok(Some(addr)),
+        }
+    }
+
+    /// Checks that writing to |addr| doesn't grow memory past `max_cells`.
+    pub(crate) fn check_grow(&self, addr: usize) -> Result<(), FaultKind> {
+        let Some(max) = self.memory_policy.max_cells else {
+            return Ok(());
+        };
+        let grows = !self.memory.contains_key(&addr)
+            && !self.devices.iter().any(|(range, _)| range.contains(&addr));
+        if grows && self.memory.len() >= max {
+            return Err(FaultKind::OutOfMemory);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Writes 7 to [rb-5], then outputs it.
+    const NEGATIVE: [i64; 7] = [21101, 3, 4, -5, 204, -5, 99];
+
+    fn run(program: &[i64], policy: MemoryPolicy) -> Intcode {
+        let mut intcode = Intcode::new(program.to_vec());
+        intcode.set_memory_policy(policy);
+        let _ = intcode.try_run();
+        intcode
+    }
+
+    #[test]
+    fn negative_addresses() {
+        let intcode = run(&NEGATIVE, MemoryPolicy::default());
+        assert!(intcode.is_halted());
+        assert_eq!(intcode.output, vec![7]);
+        assert_eq!(intcode.read_memory(-5_i64 as usize), 7);
+
+        let policy = MemoryPolicy {
+            max_address: Some(99),
+            ..Default::default()
+        };
+        let intcode = run(&NEGATIVE, policy);
+        assert_eq!(intcode.output, vec![7]);
+        assert_eq!(intcode.read_memory(95), 7);
+
+        let policy = MemoryPolicy {
+            negative: NegativeAddress::Reject,
+            ..Default::default()
+        };
+        let intcode = run(&NEGATIVE, policy);
+        assert!(intcode.is_halted());
+        assert_eq!(intcode.output, vec![0]);
+
+        let policy = MemoryPolicy {
+            negative: NegativeAddress::Error,
+            ..Default::default()
+        };
+        let mut intcode = Intcode::new(NEGATIVE.to_vec());
+        intcode.set_memory_policy(policy);
+        let fault = intcode.try_run().unwrap_err();
+        assert_eq!(fault.kind, FaultKind::NegativeAddress(-5));
+        assert_eq!(fault.pc, 0);
+        assert!(intcode.is_faulted());
+    }
+
+    #[test]
+    fn wrap_with_huge_limits() {
+        let policy = MemoryPolicy {
+            max_address: Some(usize::MAX),
+            ..Default::default()
+        };
+        let intcode = run(&NEGATIVE, policy);
+        assert_eq!(intcode.output, vec![7]);
+        assert_eq!(intcode.read_memory(usize::MAX - 4), 7);
+
+        let max = i64::MAX as usize + 10;
+        let policy = MemoryPolicy {
+            max_address: Some(max),
+            ..Default::default()
+        };
+        let intcode = run(&NEGATIVE, policy);
+        assert_eq!(intcode.output, vec![7]);
+        assert_eq!(intcode.read_memory(max - 4), 7);
+    }
+
+    #[test]
+    fn limits() {
+        // Writes to [1000].
+        let program = [1101, 1, 1, 1000, 99];
+        let policy = MemoryPolicy {
+            max_address: Some(999),
+            ..Default::default()
+        };
+        let intcode = run(&program, policy);
+        assert_eq!(
+            intcode.fault().unwrap().kind,
+            FaultKind::AddressOutOfRange(1000)
+        );
+        assert_eq!(intcode.read_memory(1000), 0);
+
+        let policy = MemoryPolicy {
+            max_cells: Some(5),
+            ..Default::default()
+        };
+        let intcode = run(&program, policy);
+        assert_eq!(intcode.fault().unwrap().kind, FaultKind::OutOfMemory);
+        // Overwriting existing cells is fine.
+        let intcode = run(&[1101, 1, 1, 3, 99], policy);
+        assert!(intcode.is_halted());
+    }
+
+    #[test]
+    fn overflow_faults() {
+        let fault = |program: &[i64]| {
+            let mut intcode = Intcode::new(program.to_vec());
+            intcode.input.push_back(1);
+            intcode.try_run().unwrap_err().kind
+        };
+        let max = i64::MAX;
+        assert_eq!(fault(&[1101, max, 1, 0, 99]), FaultKind::Overflow);
+        assert_eq!(fault(&[1102, max, 2, 0, 99]), FaultKind::Overflow);
+        assert_eq!(fault(&[109, max, 109, 1, 99]), FaultKind::Overflow);
+        // Relative parameters that read and write past the top of the address space.
+        assert_eq!(fault(&[109, max, 204, 1, 99]), FaultKind::Overflow);
+        assert_eq!(fault(&[109, max, 203, 1, 99]), FaultKind::Overflow);
+        assert_eq!(fault(&[1105, 1, -2, 99]), FaultKind::BadJump(-2));
+        assert_eq!(
+            fault(&[1106, 0, i64::MIN, 99]),
+            FaultKind::BadJump(i64::MIN)
+        );
+    }
+
+    #[test]
+    fn input_not_lost_on_fault() {
+        let mut intcode = Intcode::new(vec![3, -1, 99]);
+        intcode.set_memory_policy(MemoryPolicy {
+            negative: NegativeAddress::Error,
+            ..Default::default()
+        });
+        intcode.input.push_back(5);
+        assert!(intcode.try_run().is_err());
+        assert_eq!(intcode.input, vec![5]);
+    }
+}
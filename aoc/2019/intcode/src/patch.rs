@@ -0,0 +1,277 @@
+//! Patches for Intcode program images, e.g. inserting coins in the day 13 arcade.
+//!
+//! Patch files have one patch per line, with `#` comments:
+//!
+//! ```text
+//! 0: 1 -> 2             # set mem[0] to 2, but only if it's 1
+//! 0: * -> 2             # set mem[0] to 2 whatever it was
+//! freeze 392 = 20       # keep mem[392] at 20 after every instruction
+//! freeze 392 = 20 every 100
+//! ```
+
+use crate::Intcode;
+use std::fmt;
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Patch {
+    /// Writes |value| to |addr| once, after checking the cell holds |expected|.
+    Set {
+        addr: usize,
+        expected: Option<i64>,
+        value: i64,
+    },
+    /// Keeps |addr| at |value| by writing it again every |every| instructions.
+    Freeze { addr: usize, value: i64, every: u64 },
+}
+
+/// A `Set` patch found something other than what it expected.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PatchError {
+    pub addr: usize,
+    pub expected: i64,
+    pub found: i64,
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "mem[{}] is {}, patch expected {}",
+            self.addr, self.found, self.expected
+        )
+    }
+}
+
+impl std::error::Error for PatchError {}
+
+impl Patch {
+    fn verify(&self, read: impl Fn(usize) -> i64) -> Result<(), PatchError> {
+        if let Patch::Set {
+            addr,
+            expected: Some(expected),
+            ..
+        } = *self
+        {
+            let found = read(addr);
+            if found != expected {
+                return Err(PatchError {
+                    addr,
+                    expected,
+                    found,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn parse(line: &str) -> Option<Self> {
+        if let Some(rest) = line.strip_prefix("freeze") {
+            let (addr, rest) = rest.split_once('=')?;
+            let mut words = rest.split_whitespace();
+            let value = words.next()?.parse().ok()?;
+            let every = match (words.next(), words.next(), words.next()) {
+                (None, _, _) => 1,
+                (Some("every"), Some(n), None) => n.parse().ok().filter(|n| *n > 0)?,
+                _ => return None,
+            };
+            return Some(Patch::Freeze {
+                addr: addr.trim().parse().ok()?,
+                value,
+                every,
+            });
+        }
+
+        let (addr, rest) = line.split_once(':')?;
+        let (expected, value) = rest.split_once("->")?;
+        let expected = match expected.trim() {
+            "*" => None,
+            e => Some(e.parse().ok()?),
+        };
+        Some(Patch::Set {
+            addr: addr.trim().parse().ok()?,
+            expected,
+            value: value.trim().parse().ok()?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PatchSet {
+    pub patches: Vec<Patch>,
+}
+
+impl PatchSet {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        let text =
+            std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Self::parse(&text).map_err(|e| format!("{}:{}", path.display(), e))
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut patches = vec![];
+        for (lineno, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            match Patch::parse(line) {
+                Some(patch) => patches.push(patch),
+                None => return Err(format!("{}: bad patch {:?}", lineno + 1, line)),
+            }
+        }
+        Ok(Self { patches })
+    }
+
+    /// Applies every patch to |program|, or none of them if any `Set` patch doesn't match.
+    /// Freezes are applied once since there's nothing to re-apply them to.
+    pub fn apply(&self, program: &mut Vec<i64>) -> Result<(), PatchError> {
+        for patch in self.patches.iter() {
+            patch.verify(|addr| program.get(addr).copied().unwrap_or(0))?;
+        }
+        for patch in self.patches.iter() {
+            let (Patch::Set { addr, value, .. } | Patch::Freeze { addr, value, .. }) = *patch;
+            if program.len() <= addr {
+                program.resize(addr + 1, 0);
+            }
+            program[addr] = value;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct Freeze {
+    addr: usize,
+    value: i64,
+    every: u64,
+}
+
+impl Intcode {
+    pub fn apply_patch(&mut self, patch: &Patch) -> Result<(), PatchError> {
+        patch.verify(|addr| self.read_memory(addr))?;
+        match *patch {
+            Patch::Set { addr, value, .. } => self.write_memory(addr, value),
+            Patch::Freeze { addr, value, every } => self.freeze(addr, value, every),
+        }
+        Ok(())
+    }
+
+    /// Applies every patch, or none of them if any `Set` patch doesn't match.
+    pub fn apply_patches(&mut self, patches: &PatchSet) -> Result<(), PatchError> {
+        for patch in patches.patches.iter() {
+            patch.verify(|addr| self.read_memory(addr))?;
+        }
+        for patch in patches.patches.iter() {
+            self.apply_patch(patch)?;
+        }
+        Ok(())
+    }
+
+    /// Writes |value| to |addr| now and again after every |every| instructions. Replaces any
+    /// earlier freeze of the same cell.
+    pub fn freeze(&mut self, addr: usize, value: i64, every: u64) {
+        assert!(every > 0);
+        self.unfreeze(addr);
+        self.freezes.push(Freeze { addr, value, every });
+        self.write_memory(addr, value);
+    }
+
+    pub fn unfreeze(&mut self, addr: usize) {
+        self.freezes.retain(|f| f.addr != addr);
+    }
+
+    pub(crate) fn apply_freezes(&mut self) {
+        for i in 0..self.freezes.len() {
+            let Freeze { addr, value, every } = self.freezes[i];
+            if self.cycles.is_multiple_of(every) {
+                self.log_write(addr);
+                self.write_memory(addr, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let patches = PatchSet::parse(
+            "# coins\n\
+             0: 1 -> 2\n\
+             5: * -> -7\n\
+             freeze 392 = 20\n\
+             freeze 10=3 every 100 # slow\n",
+        )
+        .unwrap();
+        assert_eq!(
+            patches.patches,
+            vec![
+                Patch::Set {
+                    addr: 0,
+                    expected: Some(1),
+                    value: 2
+                },
+                Patch::Set {
+                    addr: 5,
+                    expected: None,
+                    value: -7
+                },
+                Patch::Freeze {
+                    addr: 392,
+                    value: 20,
+                    every: 1
+                },
+                Patch::Freeze {
+                    addr: 10,
+                    value: 3,
+                    every: 100
+                },
+            ]
+        );
+        assert_eq!(
+            PatchSet::parse("0: 1 => 2").unwrap_err(),
+            "1: bad patch \"0: 1 => 2\""
+        );
+        assert!(PatchSet::parse("freeze 1 = 2 every 0").is_err());
+    }
+
+    #[test]
+    fn apply_verifies() {
+        let patches = PatchSet::parse("0: 1 -> 2\n3: 9 -> 4").unwrap();
+        let mut program = vec![1, 0, 0, 3, 99];
+        assert_eq!(
+            patches.apply(&mut program),
+            Err(PatchError {
+                addr: 3,
+                expected: 9,
+                found: 3
+            })
+        );
+        // Nothing was applied.
+        assert_eq!(program, vec![1, 0, 0, 3, 99]);
+
+        let patches = PatchSet::parse("0: 1 -> 2\n7: 0 -> 4").unwrap();
+        patches.apply(&mut program).unwrap();
+        assert_eq!(program, vec![2, 0, 0, 3, 99, 0, 0, 4]);
+    }
+
+    #[test]
+    fn freeze() {
+        // Output [20] three times, incrementing it in between.
+        let program = vec![4, 20, 1001, 20, 1, 20, 4, 20, 1001, 20, 1, 20, 4, 20, 99];
+        let mut intcode = Intcode::new(program.clone());
+        intcode.run();
+        assert_eq!(intcode.output, vec![0, 1, 2]);
+
+        let mut intcode = Intcode::new(program);
+        intcode
+            .apply_patches(&PatchSet::parse("freeze 20 = 7").unwrap())
+            .unwrap();
+        intcode.run();
+        assert_eq!(intcode.output, vec![7, 7, 7]);
+    }
+}
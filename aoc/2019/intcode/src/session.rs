@@ -0,0 +1,129 @@
+use std::fmt;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// One value the program consumed through an input instruction.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Recorded {
+    pub value: i64,
+    /// Number of instructions the machine had executed when the value was consumed.
+    pub cycle: u64,
+    /// Wall-clock time since recording started, if timing was requested.
+    pub elapsed: Option<Duration>,
+}
+
+/// Every input a program consumed, in order. Saved as one `value cycle [elapsed_us]` line per
+/// input, with `#` comments.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Session {
+    pub inputs: Vec<Recorded>,
+}
+
+impl Session {
+    pub fn values(&self) -> impl Iterator<Item = i64> + '_ {
+        self.inputs.iter().map(|r| r.value)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        Self::parse(&std::fs::read_to_string(path)?)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut inputs = vec![];
+        for (lineno, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let bad_line = || format!("line {}: bad session entry {:?}", lineno + 1, line);
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 2 || fields.len() > 3 {
+                return Err(bad_line());
+            }
+            let value = fields[0].parse().map_err(|_| bad_line())?;
+            let cycle = fields[1].parse().map_err(|_| bad_line())?;
+            let elapsed = match fields.get(2) {
+                Some(us) => Some(Duration::from_micros(us.parse().map_err(|_| bad_line())?)),
+                None => None,
+            };
+            inputs.push(Recorded {
+                value,
+                cycle,
+                elapsed,
+            });
+        }
+        Ok(Self { inputs })
+    }
+}
+
+impl fmt::Display for Session {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "# value cycle [elapsed_us]")?;
+        for r in self.inputs.iter() {
+            match r.elapsed {
+                Some(elapsed) => writeln!(f, "{} {} {}", r.value, r.cycle, elapsed.as_micros())?,
+                None => writeln!(f, "{} {}", r.value, r.cycle)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+pub(crate) struct Recorder {
+    pub(crate) session: Session,
+    started: Option<Instant>,
+}
+
+impl Recorder {
+    pub(crate) fn new(timing: bool) -> Self {
+        Self {
+            session: Session::default(),
+            started: timing.then(Instant::now),
+        }
+    }
+
+    pub(crate) fn record(&mut self, value: i64, cycle: u64) {
+        self.session.inputs.push(Recorded {
+            value,
+            cycle,
+            elapsed: self.started.map(|start| start.elapsed()),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let session = Session {
+            inputs: vec![
+                Recorded {
+                    value: -1,
+                    cycle: 0,
+                    elapsed: None,
+                },
+                Recorded {
+                    value: 42,
+                    cycle: 17,
+                    elapsed: Some(Duration::from_micros(250)),
+                },
+            ],
+        };
+        assert_eq!(Session::parse(&session.to_string()).unwrap(), session);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(Session::parse("1 2 3 4").is_err());
+        assert!(Session::parse("one 2").is_err());
+        assert!(Session::parse("5").is_err());
+    }
+}
@@ -0,0 +1,778 @@
+//! Concolic execution: runs a program on concrete values while tracking how selected memory
+//! cells and inputs flow through `Add`/`Mul`/`LessThan`/`Equals`, then solves for symbol values
+//! that make a target come out right.
+//!
+//! The built-in solver handles one linear equation over the symbols exactly and brute-forces
+//! everything else over the symbols' domains, so it's meant for small puzzles like day 2's
+//! noun/verb search rather than general constraint solving.
+
+use crate::{Intcode, Mode, Opcode, Operation};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::rc::Rc;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Symbol {
+    /// Initial value of a memory cell.
+    Memory(usize),
+    /// The n-th value the program reads from input.
+    Input(usize),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    Const(i64),
+    Sym(Symbol),
+    Add(Rc<Expr>, Rc<Expr>),
+    Mul(Rc<Expr>, Rc<Expr>),
+    LessThan(Rc<Expr>, Rc<Expr>),
+    Equals(Rc<Expr>, Rc<Expr>),
+}
+
+/// `constant + sum(coeff * sym)`.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Linear {
+    coeffs: BTreeMap<Symbol, i64>,
+    constant: i64,
+}
+
+// The machine faults on overflow, so a form that overflows doesn't describe anything it can
+// compute. None if any part of it does.
+impl Linear {
+    fn scale(mut self, k: i64) -> Option<Self> {
+        self.constant = self.constant.checked_mul(k)?;
+        for c in self.coeffs.values_mut() {
+            *c = c.checked_mul(k)?;
+        }
+        self.coeffs.retain(|_, c| *c != 0);
+        Some(self)
+    }
+
+    fn add(mut self, other: Linear) -> Option<Self> {
+        self.constant = self.constant.checked_add(other.constant)?;
+        for (sym, c) in other.coeffs {
+            let sum = self.coeffs.entry(sym).or_insert(0);
+            *sum = sum.checked_add(c)?;
+        }
+        self.coeffs.retain(|_, c| *c != 0);
+        Some(self)
+    }
+
+    fn to_expr(&self) -> Rc<Expr> {
+        let mut terms = self.coeffs.iter().map(|(sym, c)| {
+            let sym = Rc::new(Expr::Sym(*sym));
+            if *c == 1 {
+                sym
+            } else {
+                Rc::new(Expr::Mul(Rc::new(Expr::Const(*c)), sym))
+            }
+        });
+        let Some(first) = terms.next() else {
+            return Rc::new(Expr::Const(self.constant));
+        };
+        let sum = terms.fold(first, |acc, term| Rc::new(Expr::Add(acc, term)));
+        if self.constant == 0 {
+            sum
+        } else {
+            Rc::new(Expr::Add(sum, Rc::new(Expr::Const(self.constant))))
+        }
+    }
+}
+
+impl Expr {
+    /// Value of the expression under |env|, or None if computing it overflows, which would fault
+    /// the machine.
+    pub fn eval(&self, env: &HashMap<Symbol, i64>) -> Option<i64> {
+        Some(match self {
+            Expr::Const(c) => *c,
+            Expr::Sym(sym) => env[sym],
+            Expr::Add(a, b) => a.eval(env)?.checked_add(b.eval(env)?)?,
+            Expr::Mul(a, b) => a.eval(env)?.checked_mul(b.eval(env)?)?,
+            Expr::LessThan(a, b) => (a.eval(env)? < b.eval(env)?) as i64,
+            Expr::Equals(a, b) => (a.eval(env)? == b.eval(env)?) as i64,
+        })
+    }
+
+    pub fn is_const(&self) -> bool {
+        matches!(self, Expr::Const(_))
+    }
+
+    pub fn symbols(&self) -> BTreeSet<Symbol> {
+        let mut syms = BTreeSet::new();
+        self.collect_symbols(&mut syms);
+        syms
+    }
+
+    fn collect_symbols(&self, syms: &mut BTreeSet<Symbol>) {
+        match self {
+            Expr::Const(_) => {}
+            Expr::Sym(sym) => {
+                syms.insert(*sym);
+            }
+            Expr::Add(a, b) | Expr::Mul(a, b) | Expr::LessThan(a, b) | Expr::Equals(a, b) => {
+                a.collect_symbols(syms);
+                b.collect_symbols(syms);
+            }
+        }
+    }
+
+    fn linear(&self) -> Option<Linear> {
+        match self {
+            Expr::Const(c) => Some(Linear {
+                coeffs: BTreeMap::new(),
+                constant: *c,
+            }),
+            Expr::Sym(sym) => Some(Linear {
+                coeffs: BTreeMap::from([(*sym, 1)]),
+                constant: 0,
+            }),
+            Expr::Add(a, b) => a.linear()?.add(b.linear()?),
+            Expr::Mul(a, b) => match (a.as_ref(), b.as_ref()) {
+                (Expr::Const(k), e) | (e, Expr::Const(k)) => e.linear()?.scale(*k),
+                _ => None,
+            },
+            Expr::LessThan(..) | Expr::Equals(..) => None,
+        }
+    }
+
+    // The constructors below fold constants and keep linear expressions in a canonical
+    // `c1*s1 + c2*s2 + ... + k` shape so they don't grow with every instruction.
+
+    fn add(a: Rc<Expr>, b: Rc<Expr>) -> Rc<Expr> {
+        match a.linear().zip(b.linear()).and_then(|(la, lb)| la.add(lb)) {
+            Some(sum) => sum.to_expr(),
+            None => Rc::new(Expr::Add(a, b)),
+        }
+    }
+
+    fn mul(a: Rc<Expr>, b: Rc<Expr>) -> Rc<Expr> {
+        let product = match (a.as_ref(), b.as_ref()) {
+            (Expr::Const(k), _) => b.linear().and_then(|lb| lb.scale(*k)),
+            (_, Expr::Const(k)) => a.linear().and_then(|la| la.scale(*k)),
+            _ => None,
+        };
+        match product {
+            Some(product) => product.to_expr(),
+            None => Rc::new(Expr::Mul(a, b)),
+        }
+    }
+
+    fn less_than(a: Rc<Expr>, b: Rc<Expr>) -> Rc<Expr> {
+        match (a.as_ref(), b.as_ref()) {
+            (Expr::Const(x), Expr::Const(y)) => Rc::new(Expr::Const((x < y) as i64)),
+            _ => Rc::new(Expr::LessThan(a, b)),
+        }
+    }
+
+    fn equals(a: Rc<Expr>, b: Rc<Expr>) -> Rc<Expr> {
+        match (a.as_ref(), b.as_ref()) {
+            (Expr::Const(x), Expr::Const(y)) => Rc::new(Expr::Const((x == y) as i64)),
+            _ => Rc::new(Expr::Equals(a, b)),
+        }
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Symbol::Memory(addr) => write!(f, "mem[{}]", addr),
+            Symbol::Input(n) => write!(f, "in[{}]", n),
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expr::Const(c) => write!(f, "{}", c),
+            Expr::Sym(sym) => write!(f, "{}", sym),
+            Expr::Add(a, b) => write!(f, "({} + {})", a, b),
+            Expr::Mul(a, b) => write!(f, "{} * {}", a, b),
+            Expr::LessThan(a, b) => write!(f, "({} < {})", a, b),
+            Expr::Equals(a, b) => write!(f, "({} == {})", a, b),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Constraint {
+    Equals(Rc<Expr>, i64),
+    NotEquals(Rc<Expr>, i64),
+}
+
+impl Constraint {
+    fn holds(&self, env: &HashMap<Symbol, i64>) -> bool {
+        match self {
+            Constraint::Equals(e, v) => e.eval(env) == Some(*v),
+            Constraint::NotEquals(e, v) => e.eval(env).is_some_and(|x| x != *v),
+        }
+    }
+
+    fn negate(&self) -> Self {
+        match self {
+            Constraint::Equals(e, v) => Constraint::NotEquals(e.clone(), *v),
+            Constraint::NotEquals(e, v) => Constraint::Equals(e.clone(), *v),
+        }
+    }
+
+    fn expr(&self) -> &Expr {
+        match self {
+            Constraint::Equals(e, _) | Constraint::NotEquals(e, _) => e,
+        }
+    }
+}
+
+/// A cell's concrete value alongside how it was computed. |deps| holds the assumptions the
+/// expression relies on, e.g. that a symbolic pointer pointed where it did in this run. They only
+/// become part of the path if the value ends up mattering.
+#[derive(Clone, Debug)]
+struct Value {
+    concrete: i64,
+    expr: Rc<Expr>,
+    deps: Vec<Constraint>,
+}
+
+impl Value {
+    fn constant(c: i64) -> Self {
+        Self {
+            concrete: c,
+            expr: Rc::new(Expr::Const(c)),
+            deps: vec![],
+        }
+    }
+
+    /// Constraint pinning the value to what it was in this run, plus everything it relies on.
+    fn pinned(&self) -> Vec<Constraint> {
+        let mut cs = self.deps.clone();
+        if !self.expr.is_const() {
+            cs.push(Constraint::Equals(self.expr.clone(), self.concrete));
+        }
+        cs
+    }
+}
+
+/// Where the solver looks for the answer once the program halts.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Target {
+    Memory(usize),
+    Output(usize),
+}
+
+/// Result of one concolic run.
+struct Trace {
+    memory: HashMap<usize, Value>,
+    outputs: Vec<Value>,
+    /// Branch decisions and other assumptions the run's control flow depended on, each tagged
+    /// with the pc that made it.
+    path: Vec<(usize, Constraint)>,
+    halted: bool,
+}
+
+impl Trace {
+    fn target(&self, target: Target) -> Option<Value> {
+        if !self.halted {
+            return None;
+        }
+        match target {
+            Target::Memory(addr) => Some(
+                self.memory
+                    .get(&addr)
+                    .cloned()
+                    .unwrap_or_else(|| Value::constant(0)),
+            ),
+            Target::Output(n) => self.outputs.get(n).cloned(),
+        }
+    }
+}
+
+pub struct Solver {
+    program: Vec<i64>,
+    inputs: Vec<i64>,
+    domains: BTreeMap<Symbol, RangeInclusive<i64>>,
+    /// Upper bound on concolic runs, i.e. program paths explored.
+    pub max_runs: usize,
+    /// Upper bound on instructions per run, so paths that loop forever get cut off.
+    pub max_steps: usize,
+    /// Upper bound on assignments the brute-force part of the solver tries per query.
+    pub max_candidates: usize,
+}
+
+impl Solver {
+    pub fn new(program: Vec<i64>) -> Self {
+        Self {
+            program,
+            inputs: vec![],
+            domains: BTreeMap::new(),
+            max_runs: 1000,
+            max_steps: 1_000_000,
+            max_candidates: 10_000_000,
+        }
+    }
+
+    /// Concrete values for inputs that aren't symbolic.
+    pub fn inputs(&mut self, inputs: Vec<i64>) {
+        self.inputs = inputs;
+    }
+
+    pub fn symbolic_memory(&mut self, addr: usize, domain: RangeInclusive<i64>) {
+        self.domains.insert(Symbol::Memory(addr), domain);
+    }
+
+    pub fn symbolic_input(&mut self, n: usize, domain: RangeInclusive<i64>) {
+        self.domains.insert(Symbol::Input(n), domain);
+    }
+
+    /// Symbolic value of |target| when every symbol is at the bottom of its domain.
+    pub fn target_expr(&self, target: Target) -> Option<Rc<Expr>> {
+        self.trace(&self.seed())
+            .target(target)
+            .map(|value| value.expr)
+    }
+
+    /// Finds values for the symbols that make |target| come out as |value| once the program
+    /// halts. Every answer is double-checked on a real `Intcode`.
+    pub fn solve(&self, target: Target, value: i64) -> Option<HashMap<Symbol, i64>> {
+        let mut queue = VecDeque::from([self.seed()]);
+        let mut tried = HashSet::new();
+
+        for _ in 0..self.max_runs {
+            let Some(env) = queue.pop_front() else {
+                break;
+            };
+            let trace = self.trace(&env);
+            let path: Vec<Constraint> = trace.path.iter().map(|(_, c)| c.clone()).collect();
+
+            if let Some(result) = trace.target(target) {
+                let mut cs = path.clone();
+                cs.extend(result.deps);
+                cs.push(Constraint::Equals(result.expr, value));
+                if let Some(solution) = self.solve_constraints(&cs, &env)
+                    && self.check(&solution, target, value)
+                {
+                    return Some(solution);
+                }
+            }
+
+            // Look for inputs that take the program down a different path, flipping the latest
+            // decisions first.
+            for i in (0..path.len()).rev() {
+                let key: Vec<(usize, bool)> = trace.path[..=i]
+                    .iter()
+                    .enumerate()
+                    .map(|(j, (pc, c))| (*pc, matches!(c, Constraint::Equals(..)) ^ (j == i)))
+                    .collect();
+                if !tried.insert(key) {
+                    continue;
+                }
+                let mut cs = path[..i].to_vec();
+                cs.push(path[i].negate());
+                if let Some(next) = self.solve_constraints(&cs, &env) {
+                    queue.push_back(next);
+                }
+            }
+        }
+        None
+    }
+
+    fn seed(&self) -> HashMap<Symbol, i64> {
+        self.domains
+            .iter()
+            .map(|(sym, domain)| (*sym, *domain.start()))
+            .collect()
+    }
+
+    fn check(&self, env: &HashMap<Symbol, i64>, target: Target, value: i64) -> bool {
+        let mut intcode = Intcode::new(self.program.clone());
+        let mut inputs = self.inputs.clone();
+        for (sym, val) in env.iter() {
+            match *sym {
+                Symbol::Memory(addr) => intcode.write_memory(addr, *val),
+                Symbol::Input(n) => {
+                    if inputs.len() <= n {
+                        inputs.resize(n + 1, 0);
+                    }
+                    inputs[n] = *val;
+                }
+            }
+        }
+        intcode.input = inputs.into();
+
+        for _ in 0..self.max_steps {
+            if !intcode.step() {
+                break;
+            }
+        }
+        if !intcode.is_halted() {
+            return false;
+        }
+        match target {
+            Target::Memory(addr) => intcode.read_memory(addr) == value,
+            Target::Output(n) => intcode.output.get(n) == Some(&value),
+        }
+    }
+
+    fn trace(&self, env: &HashMap<Symbol, i64>) -> Trace {
+        let mut memory: HashMap<usize, Value> = self
+            .program
+            .iter()
+            .enumerate()
+            .map(|(addr, word)| (addr, Value::constant(*word)))
+            .collect();
+        for (sym, val) in env.iter() {
+            if let Symbol::Memory(addr) = *sym {
+                memory.insert(
+                    addr,
+                    Value {
+                        concrete: *val,
+                        expr: Rc::new(Expr::Sym(*sym)),
+                        deps: vec![],
+                    },
+                );
+            }
+        }
+
+        let mut trace = Trace {
+            memory,
+            outputs: vec![],
+            path: vec![],
+            halted: false,
+        };
+        let mut pc = 0;
+        let mut relative_base: i64 = 0;
+        let mut inputs_read = 0;
+
+        let read = |memory: &HashMap<usize, Value>, addr: usize| {
+            memory
+                .get(&addr)
+                .cloned()
+                .unwrap_or_else(|| Value::constant(0))
+        };
+
+        for _ in 0..self.max_steps {
+            let word = read(&trace.memory, pc);
+            for c in word.pinned() {
+                trace.path.push((pc, c));
+            }
+            let opcode = Opcode::new(word.concrete);
+            if !opcode.is_valid() {
+                break;
+            }
+
+            // Address a parameter points at. Symbolic pointers are pinned to where they point in
+            // this run; the caller decides whether that matters.
+            // The closures below return None where the machine would fault on overflow, which
+            // ends the run without halting.
+            let param_addr =
+                move |memory: &HashMap<usize, Value>, n: usize| -> Option<(i64, Vec<Constraint>)> {
+                    let param = read(memory, pc + n);
+                    let addr = match opcode.mode(n) {
+                        Mode::Position => param.concrete,
+                        Mode::Relative => relative_base.checked_add(param.concrete)?,
+                        Mode::Immediate => unreachable!(),
+                    };
+                    Some((addr, param.pinned()))
+                };
+            let inp = move |memory: &HashMap<usize, Value>, n: usize| -> Option<Value> {
+                match opcode.mode(n) {
+                    Mode::Immediate => Some(read(memory, pc + n)),
+                    _ => {
+                        let (addr, pins) = param_addr(memory, n)?;
+                        let mut val = read(memory, addr as usize);
+                        val.deps.extend(pins);
+                        Some(val)
+                    }
+                }
+            };
+            let binary = move |memory: &HashMap<usize, Value>,
+                               f: fn(i64, i64) -> Option<i64>,
+                               g: fn(Rc<Expr>, Rc<Expr>) -> Rc<Expr>| {
+                let a = inp(memory, 1)?;
+                let b = inp(memory, 2)?;
+                let mut deps = a.deps;
+                deps.extend(b.deps);
+                Some(Value {
+                    concrete: f(a.concrete, b.concrete)?,
+                    expr: g(a.expr, b.expr),
+                    deps,
+                })
+            };
+
+            let operation = opcode.operation();
+            let result = match operation {
+                Operation::Add | Operation::Mul | Operation::LessThan | Operation::Equals => {
+                    let val = match operation {
+                        Operation::Add => binary(&trace.memory, i64::checked_add, Expr::add),
+                        Operation::Mul => binary(&trace.memory, i64::checked_mul, Expr::mul),
+                        Operation::LessThan => {
+                            binary(&trace.memory, |a, b| Some((a < b) as i64), Expr::less_than)
+                        }
+                        _ => binary(&trace.memory, |a, b| Some((a == b) as i64), Expr::equals),
+                    };
+                    let Some(val) = val else {
+                        break;
+                    };
+                    Some(val)
+                }
+                Operation::Input => {
+                    let sym = Symbol::Input(inputs_read);
+                    let val = match env.get(&sym) {
+                        Some(val) => Value {
+                            concrete: *val,
+                            expr: Rc::new(Expr::Sym(sym)),
+                            deps: vec![],
+                        },
+                        None => match self.inputs.get(inputs_read) {
+                            Some(val) => Value::constant(*val),
+                            None => break,
+                        },
+                    };
+                    inputs_read += 1;
+                    Some(val)
+                }
+                Operation::Output => {
+                    let Some(val) = inp(&trace.memory, 1) else {
+                        break;
+                    };
+                    trace.outputs.push(val);
+                    None
+                }
+                Operation::JumpIfTrue | Operation::JumpIfFalse => {
+                    let (Some(cond), Some(dst)) = (inp(&trace.memory, 1), inp(&trace.memory, 2))
+                    else {
+                        break;
+                    };
+                    trace.path.extend(cond.deps.into_iter().map(|c| (pc, c)));
+                    if !cond.expr.is_const() {
+                        let c = if cond.concrete == 0 {
+                            Constraint::Equals(cond.expr, 0)
+                        } else {
+                            Constraint::NotEquals(cond.expr, 0)
+                        };
+                        trace.path.push((pc, c));
+                    }
+                    let jump = (cond.concrete != 0) == (operation == Operation::JumpIfTrue);
+                    if jump {
+                        trace.path.extend(dst.pinned().into_iter().map(|c| (pc, c)));
+                        // Negative targets fault the machine.
+                        let Ok(target) = usize::try_from(dst.concrete) else {
+                            break;
+                        };
+                        pc = target;
+                    } else {
+                        pc += 3;
+                    }
+                    continue;
+                }
+                Operation::AdjustRelativeBase => {
+                    let Some(delta) = inp(&trace.memory, 1) else {
+                        break;
+                    };
+                    trace
+                        .path
+                        .extend(delta.pinned().into_iter().map(|c| (pc, c)));
+                    let Some(base) = relative_base.checked_add(delta.concrete) else {
+                        break;
+                    };
+                    relative_base = base;
+                    None
+                }
+                Operation::Halt => {
+                    trace.halted = true;
+                    break;
+                }
+            };
+
+            if let (Some(val), Some(dst)) = (result, operation.dst_param()) {
+                let Some((addr, pins)) = param_addr(&trace.memory, dst) else {
+                    break;
+                };
+                trace.path.extend(pins.into_iter().map(|c| (pc, c)));
+                trace.memory.insert(addr as usize, val);
+            }
+            pc += 1 + operation.num_params();
+        }
+        trace
+    }
+
+    /// Finds an assignment that satisfies |cs|. Symbols the constraints don't mention keep their
+    /// value from |base|.
+    fn solve_constraints(
+        &self,
+        cs: &[Constraint],
+        base: &HashMap<Symbol, i64>,
+    ) -> Option<HashMap<Symbol, i64>> {
+        let vars: BTreeSet<Symbol> = cs.iter().flat_map(|c| c.expr().symbols()).collect();
+        let mut env = base.clone();
+        if vars.iter().any(|sym| !self.domains.contains_key(sym)) {
+            return None;
+        }
+
+        // Solve one linear equation directly for the symbol with the widest domain and
+        // enumerate the rest.
+        let width = |sym: &Symbol| {
+            let d = &self.domains[sym];
+            d.end().saturating_sub(*d.start())
+        };
+        let pivot = cs.iter().find_map(|c| match c {
+            Constraint::Equals(e, v) => {
+                let lin = e.linear()?;
+                let sym = *lin.coeffs.keys().max_by_key(|sym| width(sym))?;
+                Some((sym, lin, *v))
+            }
+            _ => None,
+        });
+        let free: Vec<Symbol> = vars
+            .iter()
+            .filter(|sym| pivot.as_ref().map(|p| p.0) != Some(**sym))
+            .copied()
+            .collect();
+
+        let mut candidate: Vec<i64> = free.iter().map(|sym| *self.domains[sym].start()).collect();
+        for _ in 0..self.max_candidates {
+            for (sym, val) in free.iter().zip(candidate.iter()) {
+                env.insert(*sym, *val);
+            }
+
+            let solved = match &pivot {
+                Some((sym, lin, target)) => {
+                    let coeff = lin.coeffs[sym];
+                    let rest = lin
+                        .coeffs
+                        .iter()
+                        .filter(|(s, _)| *s != sym)
+                        .try_fold(lin.constant, |acc, (s, c)| {
+                            acc.checked_add(c.checked_mul(env[s])?)
+                        });
+                    let need = rest.and_then(|rest| target.checked_sub(rest));
+                    // checked_rem is None for i64::MIN % -1, where the division would overflow.
+                    let val = need.filter(|need| need.checked_rem(coeff) == Some(0));
+                    match val.map(|need| need / coeff) {
+                        Some(val) if self.domains[sym].contains(&val) => {
+                            env.insert(*sym, val);
+                            true
+                        }
+                        _ => false,
+                    }
+                }
+                None => true,
+            };
+            if solved && cs.iter().all(|c| c.holds(&env)) {
+                return Some(env);
+            }
+
+            // Odometer over the free symbols' domains.
+            let mut i = 0;
+            loop {
+                if i == free.len() {
+                    return None;
+                }
+                let domain = &self.domains[&free[i]];
+                if candidate[i] < *domain.end() {
+                    candidate[i] += 1;
+                    break;
+                }
+                candidate[i] = *domain.start();
+                i += 1;
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn linear_target() {
+        // mem[0] = mem[9] * 3 + mem[10]
+        let program = vec![1002, 9, 3, 0, 1, 0, 10, 0, 99, 0, 0];
+        let mut solver = Solver::new(program);
+        solver.symbolic_memory(9, 0..=99);
+        solver.symbolic_memory(10, 0..=2);
+
+        let expr = solver.target_expr(Target::Memory(0)).unwrap();
+        assert_eq!(expr.to_string(), "(3 * mem[9] + mem[10])");
+
+        let solution = solver.solve(Target::Memory(0), 200).unwrap();
+        let a = solution[&Symbol::Memory(9)];
+        let b = solution[&Symbol::Memory(10)];
+        assert_eq!(3 * a + b, 200);
+        assert!(solver.solve(Target::Memory(0), 1000).is_none());
+    }
+
+    #[test]
+    fn overflowing_coefficients() {
+        // mem[0] = mem[9] * 2^62, then doubled, so the coefficient doesn't fit in an i64.
+        let program = vec![1002, 9, 1 << 62, 0, 1, 0, 0, 0, 99, 0];
+        // Only 0 fits: 2 would come out as 0 if the machine wrapped, but it faults instead.
+        let mut solver = Solver::new(program.clone());
+        solver.symbolic_memory(9, 0..=3);
+        let solution = solver.solve(Target::Memory(0), 0).unwrap();
+        assert_eq!(solution[&Symbol::Memory(9)], 0);
+        let mut solver = Solver::new(program);
+        solver.symbolic_memory(9, 1..=3);
+        assert!(solver.solve(Target::Memory(0), 0).is_none());
+
+        // mem[0] = mem[9] * 2^61 + 2^62, which overflows for 2 and 3. If the machine wrapped, 3
+        // would give i64::MIN + 2^61.
+        let program = vec![1002, 9, 1 << 61, 0, 1001, 0, 1 << 62, 0, 99, 0];
+        let mut solver = Solver::new(program);
+        solver.symbolic_memory(9, -3..=3);
+        let solution = solver.solve(Target::Memory(0), 1 << 61).unwrap();
+        assert_eq!(solution[&Symbol::Memory(9)], -1);
+        assert!(
+            solver
+                .solve(Target::Memory(0), i64::MIN + (1 << 61))
+                .is_none()
+        );
+
+        // mem[0] = -mem[9], where solving for i64::MIN would divide i64::MIN by -1.
+        let mut solver = Solver::new(vec![1002, 5, -1, 0, 99, 0]);
+        solver.symbolic_memory(5, 0..=3);
+        assert!(solver.solve(Target::Memory(0), i64::MIN).is_none());
+        assert_eq!(
+            solver.solve(Target::Memory(0), -2).unwrap()[&Symbol::Memory(5)],
+            2
+        );
+    }
+
+    #[test]
+    fn symbolic_pointers() {
+        // Like day 2: mem[1] and mem[2] are first used as pointers, but that result is
+        // overwritten before it matters.
+        let program = vec![1, 0, 0, 3, 1, 1, 2, 3, 1002, 3, 10, 0, 99];
+        let mut solver = Solver::new(program);
+        solver.symbolic_memory(1, 0..=12);
+        solver.symbolic_memory(2, 0..=12);
+
+        let solution = solver.solve(Target::Memory(0), 170).unwrap();
+        assert_eq!(
+            solution[&Symbol::Memory(1)] + solution[&Symbol::Memory(2)],
+            17
+        );
+    }
+
+    #[test]
+    fn branch_on_input() {
+        // Outputs 1 only if the input equals 1234, otherwise 0.
+        let program = vec![3, 20, 1008, 20, 1234, 21, 4, 21, 99];
+        let mut solver = Solver::new(program);
+        solver.symbolic_input(0, -5000..=5000);
+        let solution = solver.solve(Target::Output(0), 1).unwrap();
+        assert_eq!(solution[&Symbol::Input(0)], 1234);
+    }
+
+    #[test]
+    fn flips_branches() {
+        // if in[0] < 10 { halt } else { out(in[0] * 2) }
+        let program = vec![
+            3, 30, 1007, 30, 10, 31, 1005, 31, 15, 1002, 30, 2, 32, 4, 32, 99, 0, 0, 0,
+        ];
+        let mut solver = Solver::new(program);
+        solver.symbolic_input(0, 0..=100);
+        let solution = solver.solve(Target::Output(0), 84).unwrap();
+        assert_eq!(solution[&Symbol::Input(0)], 42);
+    }
+}
@@ -0,0 +1,218 @@
+//! Declarative test cases for Intcode programs.
+//!
+//! A vector file holds any number of cases. Each starts with a `[name]` header followed by
+//! `key: value` lines:
+//!
+//! ```text
+//! # Day 5: is the input equal to 8?
+//! [eq8-position]
+//! program: 3,9,8,9,10,9,4,9,99,-1,8
+//! input: 8
+//! output: 1
+//! memory: 9=1
+//! ```
+//!
+//! `program` may be repeated to spread a long program over several lines. `output` is compared
+//! against everything the program printed, `memory` lists `addr=value` cells to check and `state`
+//! is `halted` (the default) or `waiting` for programs expected to block on input. `cycles` caps
+//! how long the program may run before the case fails, 10,000,000 by default.
+
+use crate::{Intcode, loader};
+use std::path::Path;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Case {
+    pub name: String,
+    /// Line of the `[name]` header, for error messages.
+    pub line: usize,
+    pub program: Vec<i64>,
+    pub input: Vec<i64>,
+    pub output: Option<Vec<i64>>,
+    pub memory: Vec<(usize, i64)>,
+    pub halts: bool,
+    /// Cycles the program gets before it's reported as looping forever.
+    pub max_cycles: u64,
+}
+
+impl Case {
+    fn new(name: &str, line: usize) -> Self {
+        Self {
+            name: name.to_string(),
+            line,
+            program: vec![],
+            input: vec![],
+            output: None,
+            memory: vec![],
+            halts: true,
+            max_cycles: 10_000_000,
+        }
+    }
+
+    /// Runs the case, describing the first expectation that didn't hold.
+    pub fn run(&self) -> Result<(), String> {
+        let mut intcode = Intcode::new(self.program.clone());
+        intcode.input = self.input.clone().into();
+        match intcode.try_run_for(self.max_cycles) {
+            Ok(true) => {}
+            Ok(false) => return Err(format!("still running after {} cycles", self.max_cycles)),
+            Err(fault) => return Err(fault.to_string()),
+        }
+
+        if self.halts != intcode.is_halted() {
+            return Err(format!(
+                "expected the program to be {}",
+                if self.halts { "halted" } else { "waiting" }
+            ));
+        }
+        if let Some(expected) = &self.output {
+            let actual: Vec<i64> = intcode.output.iter().copied().collect();
+            if actual != *expected {
+                return Err(format!("output {:?}, expected {:?}", actual, expected));
+            }
+        }
+        for (addr, expected) in self.memory.iter() {
+            let actual = intcode.read_memory(*addr);
+            if actual != *expected {
+                return Err(format!("mem[{}] = {}, expected {}", addr, actual, expected));
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn load<P: AsRef<Path>>(path: P) -> Result<Vec<Case>, String> {
+    let path = path.as_ref();
+    let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    parse(&text).map_err(|e| format!("{}:{}", path.display(), e))
+}
+
+pub fn parse(text: &str) -> Result<Vec<Case>, String> {
+    let mut cases: Vec<Case> = vec![];
+    for (lineno, line) in text.lines().enumerate() {
+        let lineno = lineno + 1;
+        let err = |msg: String| format!("{}: {}", lineno, msg);
+        let line = line.split('#').next().unwrap().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            cases.push(Case::new(name.trim(), lineno));
+            continue;
+        }
+
+        let Some(case) = cases.last_mut() else {
+            return Err(err(
+                "expected a [name] header before the first case".to_string()
+            ));
+        };
+        let Some((key, value)) = line.split_once(':') else {
+            return Err(err(format!("expected `key: value`, got {:?}", line)));
+        };
+        let words =
+            |value: &str| loader::parse_text(value).map_err(|e| err(format!("bad {}: {}", key, e)));
+        match key.trim() {
+            "program" => case.program.extend(words(value)?),
+            "input" => case.input.extend(words(value)?),
+            "output" => case
+                .output
+                .get_or_insert_with(Vec::new)
+                .extend(words(value)?),
+            "memory" => {
+                for cell in value.split([',', ' ']).filter(|c| !c.is_empty()) {
+                    let parsed = cell
+                        .split_once('=')
+                        .and_then(|(a, v)| Some((a.trim().parse().ok()?, v.trim().parse().ok()?)));
+                    match parsed {
+                        Some(cell) => case.memory.push(cell),
+                        None => return Err(err(format!("bad memory cell {:?}", cell))),
+                    }
+                }
+            }
+            "state" => {
+                case.halts = match value.trim() {
+                    "halted" => true,
+                    "waiting" => false,
+                    other => return Err(err(format!("unknown state {:?}", other))),
+                }
+            }
+            "cycles" => match value.trim().parse() {
+                Ok(cycles) => case.max_cycles = cycles,
+                Err(_) => return Err(err(format!("bad cycles {:?}", value.trim()))),
+            },
+            other => return Err(err(format!("unknown key {:?}", other))),
+        }
+    }
+
+    if let Some(case) = cases.iter().find(|c| c.program.is_empty()) {
+        return Err(format!(
+            "{}: case {:?} has no program",
+            case.line, case.name
+        ));
+    }
+    Ok(cases)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_cases() {
+        let cases = parse(
+            "# comment\n\
+             [first]\n\
+             program: 3,0,4,0\n\
+             program: 99\n\
+             input: 5\n\
+             output: 5\n\
+             memory: 0=5, 4=99\n\
+             \n\
+             [second]\n\
+             program: 3,0,99\n\
+             state: waiting\n",
+        )
+        .unwrap();
+        assert_eq!(cases.len(), 2);
+        assert_eq!(cases[0].program, vec![3, 0, 4, 0, 99]);
+        assert_eq!(cases[0].memory, vec![(0, 5), (4, 99)]);
+        assert_eq!(cases[1].line, 9);
+        assert!(!cases[1].halts);
+        assert!(cases.iter().all(|c| c.run().is_ok()));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            parse("program: 99").unwrap_err(),
+            "1: expected a [name] header before the first case"
+        );
+        assert!(
+            parse("[a]\nprogram: 1,x")
+                .unwrap_err()
+                .starts_with("2: bad program")
+        );
+        assert!(parse("[a]\nprogram: 99\nmemory: 1").is_err());
+        assert!(parse("[a]\ninput: 1").is_err());
+    }
+
+    #[test]
+    fn failing_case() {
+        let cases = parse("[a]\nprogram: 104,1,99\noutput: 2").unwrap();
+        assert_eq!(cases[0].run().unwrap_err(), "output [1], expected [2]");
+    }
+
+    #[test]
+    fn faulting_and_looping_cases() {
+        let cases =
+            parse("[bad]\nprogram: 104,1,42\n[loop]\nprogram: 1105,1,0\ncycles: 1000").unwrap();
+        let fault = Intcode::new(cases[0].program.clone())
+            .try_run()
+            .unwrap_err();
+        assert_eq!(cases[0].run().unwrap_err(), fault.to_string());
+        assert_eq!(
+            cases[1].run().unwrap_err(),
+            "still running after 1000 cycles"
+        );
+    }
+}
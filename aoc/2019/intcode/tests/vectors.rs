@@ -0,0 +1,39 @@
+use std::path::Path;
+
+/// Runs every case in every `tests/vectors/*.icv` file.
+#[test]
+fn vectors() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/vectors");
+    let mut paths: Vec<_> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "icv"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty(), "no vector files in {}", dir.display());
+
+    let mut failures = vec![];
+    let mut num_cases = 0;
+    for path in paths {
+        let cases = intcode::vectors::load(&path).unwrap_or_else(|e| panic!("{}", e));
+        for case in cases {
+            num_cases += 1;
+            if let Err(e) = case.run() {
+                failures.push(format!(
+                    "{}:{} [{}]: {}",
+                    path.display(),
+                    case.line,
+                    case.name,
+                    e
+                ));
+            }
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "{} of {} cases failed:\n{}",
+        failures.len(),
+        num_cases,
+        failures.join("\n")
+    );
+}
@@ -0,0 +1,130 @@
+//! Compares the arena list with the allocating ones on push/pop/iterate workloads.
+//!
+//! Run with `cargo bench`. Each workload reports the best of a few rounds, since allocator noise
+//! only ever makes a round slower.
+
+use list::{arena, fourth, second, sixth};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const N: u64 = 1_000_000;
+const ROUNDS: usize = 5;
+
+fn bench(workload: &str, list: &str, mut f: impl FnMut() -> u64) {
+    let mut best = Duration::MAX;
+    for _ in 0..ROUNDS {
+        let start = Instant::now();
+        black_box(f());
+        best = best.min(start.elapsed());
+    }
+    println!(
+        "{:<12} {:<8} {:>10.3?} {:>7.2} ns/op",
+        workload,
+        list,
+        best,
+        best.as_nanos() as f64 / N as f64
+    );
+}
+
+/// Pushes N elements onto the front, then pops them all.
+fn stack() {
+    bench("stack", "second", || {
+        let mut list = second::List::new();
+        for i in 0..N {
+            list.push(i);
+        }
+        let mut sum = 0;
+        while let Some(i) = list.pop() {
+            sum += i;
+        }
+        sum
+    });
+    bench("stack", "fourth", || {
+        let mut list = fourth::List::new();
+        for i in 0..N {
+            list.push_front(i);
+        }
+        let mut sum = 0;
+        while let Some(i) = list.pop_front() {
+            sum += i;
+        }
+        sum
+    });
+    bench("stack", "sixth", || {
+        let mut list = sixth::List::new();
+        for i in 0..N {
+            list.push_front(i);
+        }
+        let mut sum = 0;
+        while let Some(i) = list.pop_front() {
+            sum += i;
+        }
+        sum
+    });
+    bench("stack", "arena", || {
+        let mut list = arena::List::new();
+        for i in 0..N {
+            list.push_front(i);
+        }
+        let mut sum = 0;
+        while let Some(i) = list.pop_front() {
+            sum += i;
+        }
+        sum
+    });
+}
+
+/// Keeps a thousand elements queued while N go through, like a work queue would.
+fn churn() {
+    const QUEUED: u64 = 1000;
+    bench("churn", "fourth", || {
+        let mut list = fourth::List::new();
+        list.extend(0..QUEUED);
+        let mut sum = 0;
+        for i in 0..N {
+            list.push_back(i);
+            sum += list.pop_front().unwrap();
+        }
+        sum
+    });
+    bench("churn", "sixth", || {
+        let mut list = sixth::List::new();
+        list.extend(0..QUEUED);
+        let mut sum = 0;
+        for i in 0..N {
+            list.push_back(i);
+            sum += list.pop_front().unwrap();
+        }
+        sum
+    });
+    bench("churn", "arena", || {
+        let mut list = arena::List::new();
+        list.extend(0..QUEUED);
+        let mut sum = 0;
+        for i in 0..N {
+            list.push_back(i);
+            sum += list.pop_front().unwrap();
+        }
+        sum
+    });
+}
+
+/// Sums a list of N elements.
+fn iterate() {
+    let list: second::List<u64> = (0..N).collect();
+    bench("iterate", "second", || list.iter().sum());
+    let list: fourth::List<u64> = (0..N).collect();
+    bench("iterate", "fourth", || {
+        list.iter().map(|e| *e.borrow()).sum()
+    });
+    let list: sixth::List<u64> = (0..N).collect();
+    bench("iterate", "sixth", || list.iter().sum());
+    let list: arena::List<u64> = (0..N).collect();
+    bench("iterate", "arena", || list.iter().sum());
+}
+
+fn main() {
+    stack();
+    churn();
+    iterate();
+}
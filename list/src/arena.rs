@@ -0,0 +1,709 @@
+use std::cmp::Ordering;
+use std::fmt::{self, Debug};
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::mem;
+
+/// A deque like `fourth::List`, with its nodes in one `Vec` instead of an allocation each.
+///
+/// Links are indices into the `Vec`. Popped nodes' slots go on a free list for the next push to
+/// reuse, so a list that churns through elements stops allocating once it's reached its largest
+/// size. The slots are only given back by `shrink_to_fit`, or dropping the list.
+pub struct List<T> {
+    slots: Vec<Slot<T>>,
+    head: Link,
+    tail: Link,
+    free: Link,
+    len: usize,
+}
+
+type Link = Option<usize>;
+
+enum Slot<T> {
+    Used(Node<T>),
+    Free { next: Link },
+}
+
+struct Node<T> {
+    elem: T,
+    prev: Link,
+    next: Link,
+}
+
+impl<T> List<T> {
+    pub fn new() -> Self {
+        List {
+            slots: Vec::new(),
+            head: None,
+            tail: None,
+            free: None,
+            len: 0,
+        }
+    }
+
+    /// A list with room for |capacity| elements before it allocates.
+    pub fn with_capacity(capacity: usize) -> Self {
+        List {
+            slots: Vec::with_capacity(capacity),
+            ..List::new()
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.slots.capacity()
+    }
+
+    fn node(&self, index: usize) -> &Node<T> {
+        match &self.slots[index] {
+            Slot::Used(node) => node,
+            Slot::Free { .. } => unreachable!("link to free slot {}", index),
+        }
+    }
+
+    fn node_mut(&mut self, index: usize) -> &mut Node<T> {
+        match &mut self.slots[index] {
+            Slot::Used(node) => node,
+            Slot::Free { .. } => unreachable!("link to free slot {}", index),
+        }
+    }
+
+    /// Puts |node| in a free slot, or a new one if there aren't any.
+    fn alloc(&mut self, node: Node<T>) -> usize {
+        self.len += 1;
+        match self.free {
+            Some(index) => {
+                let Slot::Free { next } = mem::replace(&mut self.slots[index], Slot::Used(node))
+                else {
+                    unreachable!("free list points at used slot {}", index);
+                };
+                self.free = next;
+                index
+            }
+            None => {
+                self.slots.push(Slot::Used(node));
+                self.slots.len() - 1
+            }
+        }
+    }
+
+    fn release(&mut self, index: usize) -> Node<T> {
+        self.len -= 1;
+        let slot = mem::replace(&mut self.slots[index], Slot::Free { next: self.free });
+        self.free = Some(index);
+        match slot {
+            Slot::Used(node) => node,
+            Slot::Free { .. } => unreachable!("released free slot {}", index),
+        }
+    }
+
+    pub fn push_front(&mut self, elem: T) {
+        let index = self.alloc(Node {
+            elem,
+            prev: None,
+            next: self.head,
+        });
+        match self.head {
+            Some(old_head) => self.node_mut(old_head).prev = Some(index),
+            None => self.tail = Some(index),
+        }
+        self.head = Some(index);
+    }
+
+    pub fn push_back(&mut self, elem: T) {
+        let index = self.alloc(Node {
+            elem,
+            prev: self.tail,
+            next: None,
+        });
+        match self.tail {
+            Some(old_tail) => self.node_mut(old_tail).next = Some(index),
+            None => self.head = Some(index),
+        }
+        self.tail = Some(index);
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.head.map(|index| {
+            let node = self.release(index);
+            self.head = node.next;
+            match node.next {
+                Some(new_head) => self.node_mut(new_head).prev = None,
+                None => self.tail = None,
+            }
+            node.elem
+        })
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.tail.map(|index| {
+            let node = self.release(index);
+            self.tail = node.prev;
+            match node.prev {
+                Some(new_tail) => self.node_mut(new_tail).next = None,
+                None => self.head = None,
+            }
+            node.elem
+        })
+    }
+
+    pub fn peek_front(&self) -> Option<&T> {
+        self.head.map(|index| &self.node(index).elem)
+    }
+
+    pub fn peek_front_mut(&mut self) -> Option<&mut T> {
+        self.head.map(|index| &mut self.node_mut(index).elem)
+    }
+
+    pub fn peek_back(&self) -> Option<&T> {
+        self.tail.map(|index| &self.node(index).elem)
+    }
+
+    pub fn peek_back_mut(&mut self) -> Option<&mut T> {
+        self.tail.map(|index| &mut self.node_mut(index).elem)
+    }
+
+    /// Reverses the list in place by swapping every node's links.
+    pub fn reverse(&mut self) {
+        let mut cur = self.head;
+        while let Some(index) = cur {
+            let node = self.node_mut(index);
+            mem::swap(&mut node.prev, &mut node.next);
+            cur = node.prev;
+        }
+        mem::swap(&mut self.head, &mut self.tail);
+    }
+
+    /// Stable merge sort. Runs are merged bottom-up through the `next` links, and the `prev`
+    /// links are fixed up afterwards, so nothing moves in the slab.
+    pub fn sort_by(&mut self, mut cmp: impl FnMut(&T, &T) -> Ordering) {
+        let mut width = 1;
+        while width < self.len {
+            let mut rest = self.head.take();
+            let mut tail: Link = None;
+            while rest.is_some() {
+                let right = self.cut(rest, width);
+                let next_rest = self.cut(right, width);
+                let (head, run_tail) = self.merge_chains(rest, right, &mut cmp);
+                match tail {
+                    Some(tail) => self.node_mut(tail).next = head,
+                    None => self.head = head,
+                }
+                tail = run_tail;
+                rest = next_rest;
+            }
+            width *= 2;
+        }
+        self.link_prevs();
+    }
+
+    /// Merges the sorted |other| into this sorted list. Where elements are equal, ours go first.
+    pub fn merge_by(&mut self, other: List<T>, mut cmp: impl FnMut(&T, &T) -> Ordering) {
+        // |other|'s nodes live in its own slab, so they're moved into ours as a chain first.
+        let mut right = None;
+        let mut right_tail: Link = None;
+        for elem in other {
+            let index = self.alloc(Node {
+                elem,
+                prev: None,
+                next: None,
+            });
+            match right_tail {
+                Some(tail) => self.node_mut(tail).next = Some(index),
+                None => right = Some(index),
+            }
+            right_tail = Some(index);
+        }
+        let (head, _) = self.merge_chains(self.head, right, &mut cmp);
+        self.head = head;
+        self.link_prevs();
+    }
+
+    /// Cuts the chain starting at |head| after |n| nodes and returns the rest. Only `next` links
+    /// are touched.
+    fn cut(&mut self, head: Link, n: usize) -> Link {
+        let mut index = head?;
+        for _ in 1..n {
+            index = self.node(index).next?;
+        }
+        self.node_mut(index).next.take()
+    }
+
+    /// Merges two sorted chains along their `next` links, taking from |left| first when elements
+    /// are equal. Returns the head and tail of the result.
+    fn merge_chains(
+        &mut self,
+        mut left: Link,
+        mut right: Link,
+        cmp: &mut impl FnMut(&T, &T) -> Ordering,
+    ) -> (Link, Link) {
+        let mut head: Link = None;
+        let mut tail: Link = None;
+        loop {
+            let take_right = match (left, right) {
+                (Some(l), Some(r)) => cmp(&self.node(r).elem, &self.node(l).elem) == Ordering::Less,
+                (Some(_), None) => false,
+                (None, Some(_)) => true,
+                (None, None) => break,
+            };
+            let from = if take_right { &mut right } else { &mut left };
+            let index = from.unwrap();
+            *from = self.node_mut(index).next.take();
+            match tail {
+                Some(tail) => self.node_mut(tail).next = Some(index),
+                None => head = Some(index),
+            }
+            tail = Some(index);
+        }
+        (head, tail)
+    }
+
+    /// Points every `prev` link, and the tail, back along the `next` links.
+    fn link_prevs(&mut self) {
+        let mut prev: Link = None;
+        let mut cur = self.head;
+        while let Some(index) = cur {
+            let node = self.node_mut(index);
+            node.prev = prev;
+            cur = node.next;
+            prev = Some(index);
+        }
+        self.tail = prev;
+    }
+
+    /// Removes every element, but keeps the slots for reuse.
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    /// Moves the elements into a slab with no free slots, in order, and frees the spare capacity.
+    pub fn shrink_to_fit(&mut self) {
+        let mut compact = List::with_capacity(self.len);
+        compact.extend(mem::take(self));
+        *self = compact;
+        self.slots.shrink_to_fit();
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            list: self,
+            front: self.head,
+            back: self.tail,
+            len: self.len,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            slots: self.slots.as_mut_ptr(),
+            front: self.head,
+            back: self.tail,
+            len: self.len,
+            _list: PhantomData,
+        }
+    }
+}
+
+impl<T: Ord> List<T> {
+    pub fn sort(&mut self) {
+        self.sort_by(T::cmp);
+    }
+
+    pub fn merge(&mut self, other: List<T>) {
+        self.merge_by(other, T::cmp);
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> Clone for List<T> {
+    fn clone(&self) -> Self {
+        let mut list = List::with_capacity(self.len);
+        list.extend(self.iter().cloned());
+        list
+    }
+}
+
+impl<T> Extend<T> for List<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.push_back(elem);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T: Debug> Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for List<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for List<T> {}
+
+impl<T: PartialOrd> PartialOrd for List<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<T: Ord> Ord for List<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+impl<T: Hash> Hash for List<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len.hash(state);
+        for elem in self {
+            elem.hash(state);
+        }
+    }
+}
+
+pub struct IntoIter<T>(List<T>);
+
+impl<T> IntoIterator for List<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter(self)
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.0.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<T> {
+        self.0.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+pub struct Iter<'a, T> {
+    list: &'a List<T>,
+    front: Link,
+    back: Link,
+    // Stops the two ends from passing each other.
+    len: usize,
+}
+
+impl<'a, T> IntoIterator for &'a List<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.front.map(|index| {
+            let node = self.list.node(index);
+            self.len -= 1;
+            self.front = node.next;
+            &node.elem
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.back.map(|index| {
+            let node = self.list.node(index);
+            self.len -= 1;
+            self.back = node.prev;
+            &node.elem
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+/// Hands out `&mut`s to different slots of the same `Vec`, which safe code can't do by index.
+pub struct IterMut<'a, T> {
+    slots: *mut Slot<T>,
+    front: Link,
+    back: Link,
+    len: usize,
+    _list: PhantomData<&'a mut List<T>>,
+}
+
+impl<'a, T> IntoIterator for &'a mut List<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+impl<'a, T> IterMut<'a, T> {
+    /// The node at |index|. Each one is only handed out once, since `len` stops the ends at the
+    /// same node.
+    fn node(&mut self, index: usize) -> &'a mut Node<T> {
+        match unsafe { &mut *self.slots.add(index) } {
+            Slot::Used(node) => node,
+            Slot::Free { .. } => unreachable!("link to free slot {}", index),
+        }
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.front.map(|index| {
+            let node = self.node(index);
+            self.len -= 1;
+            self.front = node.next;
+            &mut node.elem
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.back.map(|index| {
+            let node = self.node(index);
+            self.len -= 1;
+            self.back = node.prev;
+            &mut node.elem
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+unsafe impl<T: Send> Send for IterMut<'_, T> {}
+unsafe impl<T: Sync> Sync for IterMut<'_, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn basics() {
+        let mut list = List::new();
+        assert_eq!(list.pop_front(), None);
+        assert_eq!(list.pop_back(), None);
+
+        list.push_front(1);
+        list.push_front(2);
+        list.push_back(3);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.peek_front(), Some(&2));
+        assert_eq!(list.peek_back(), Some(&3));
+
+        assert_eq!(list.pop_front(), Some(2));
+        assert_eq!(list.pop_back(), Some(3));
+        assert_eq!(list.pop_back(), Some(1));
+        assert_eq!(list.pop_front(), None);
+        assert!(list.is_empty());
+
+        list.push_back(4);
+        *list.peek_front_mut().unwrap() += 1;
+        *list.peek_back_mut().unwrap() *= 10;
+        assert_eq!(list.pop_front(), Some(50));
+    }
+
+    #[test]
+    fn reuses_slots() {
+        let mut list = List::with_capacity(4);
+        for round in 0..1000 {
+            list.push_back(round);
+            list.push_front(round);
+            list.push_back(round);
+            assert_eq!(list.pop_front(), Some(round));
+            assert_eq!(list.pop_back(), Some(round));
+        }
+        assert_eq!(list.len(), 1000);
+        assert_eq!(list.slots.len(), 1002);
+
+        list.clear();
+        assert_eq!(list.slots.len(), 1002);
+        list.extend(0..1002);
+        assert_eq!(list.slots.len(), 1002);
+    }
+
+    #[test]
+    fn shrink_to_fit() {
+        let mut list: List<i32> = (0..100).collect();
+        for _ in 0..90 {
+            list.pop_front();
+        }
+        list.shrink_to_fit();
+        assert_eq!(list.slots.len(), 10);
+        assert_eq!(list, (90..100).collect());
+    }
+
+    #[test]
+    fn iter() {
+        let mut list: List<i32> = (1..=5).collect();
+        list.pop_front();
+        list.push_front(0);
+
+        let mut iter = list.iter();
+        assert_eq!(iter.len(), 5);
+        assert_eq!(iter.next(), Some(&0));
+        assert_eq!(iter.next_back(), Some(&5));
+        assert_eq!(iter.next(), Some(&2));
+        assert_eq!(iter.next_back(), Some(&4));
+        assert_eq!(iter.next(), Some(&3));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+
+        for elem in list.iter_mut().rev().step_by(2) {
+            *elem *= 10;
+        }
+        assert_eq!(list, List::from_iter([0, 2, 30, 4, 50]));
+        let mut iter = list.iter_mut();
+        assert_eq!(iter.next_back(), Some(&mut 50));
+        assert_eq!(iter.next(), Some(&mut 0));
+        assert_eq!(iter.count(), 3);
+
+        assert_eq!(
+            list.into_iter().rev().collect::<Vec<_>>(),
+            vec![50, 4, 30, 2, 0]
+        );
+    }
+
+    #[test]
+    fn sort() {
+        for len in [0, 1, 2, 3, 7, 64, 1001] {
+            let elems: Vec<u32> = (0..len)
+                .map(|i: u32| i.wrapping_mul(2_654_435_761) % 1000)
+                .collect();
+            let mut list: List<u32> = elems.iter().copied().collect();
+            list.sort();
+            let mut expected = elems.clone();
+            expected.sort();
+            assert_eq!(list.iter().copied().collect::<Vec<_>>(), expected);
+
+            // The back links have to agree too.
+            expected.reverse();
+            assert_eq!(list.into_iter().rev().collect::<Vec<_>>(), expected);
+        }
+
+        // Equal keys keep their order.
+        let mut list: List<(u32, usize)> = [3, 1, 3, 2, 1, 3].into_iter().zip(0..).collect();
+        list.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            list.into_iter().collect::<Vec<_>>(),
+            vec![(1, 1), (1, 4), (2, 3), (3, 0), (3, 2), (3, 5)]
+        );
+
+        // Sorting relinks nodes where they are, so freed slots are still there to reuse.
+        let mut list: List<i32> = (0..10).rev().collect();
+        list.pop_front();
+        list.pop_back();
+        list.sort();
+        list.push_front(-1);
+        list.push_back(9);
+        assert_eq!(list.slots.len(), 10);
+        assert_eq!(list, (-1..=9).filter(|x| *x != 0).collect());
+    }
+
+    #[test]
+    fn merge() {
+        let mut list: List<(u32, char)> = [(1, 'a'), (3, 'a'), (8, 'a')].into_iter().collect();
+        list.merge_by([(0, 'x'), (3, 'x')].into_iter().collect(), |a, b| {
+            a.0.cmp(&b.0)
+        });
+        assert_eq!(
+            list.into_iter().rev().collect::<Vec<_>>(),
+            vec![(8, 'a'), (3, 'x'), (3, 'a'), (1, 'a'), (0, 'x')]
+        );
+
+        let mut list = List::new();
+        list.merge((1..=3).collect());
+        list.merge(List::new());
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(list.peek_back(), Some(&3));
+
+        // The other list's elements move into free slots first.
+        list.pop_front();
+        list.merge([0, 4].into_iter().collect());
+        assert_eq!(list.slots.len(), 4);
+        assert_eq!(list.len(), 4);
+        assert_eq!(list, List::from_iter([0, 2, 3, 4]));
+    }
+
+    #[test]
+    fn reverse_and_traits() {
+        let mut list: List<i32> = (1..=4).collect();
+        list.reverse();
+        assert_eq!(format!("{:?}", list), "[4, 3, 2, 1]");
+        assert_eq!(list.pop_back(), Some(1));
+        list.push_back(0);
+        assert_eq!(list.iter().rev().copied().collect::<Vec<_>>(), [0, 2, 3, 4]);
+
+        assert_eq!(list.clone(), list);
+        assert!(list > (1..=4).collect());
+        assert!(List::new() < list);
+
+        let mut set = std::collections::HashSet::new();
+        assert!(set.insert(list.clone()));
+        assert!(!set.insert(list));
+    }
+}
@@ -1,4 +1,9 @@
 use std::cell::{Ref, RefCell, RefMut};
+use std::cmp::Ordering;
+use std::fmt::{self, Debug};
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::mem;
 use std::rc::Rc;
 
 pub struct List<T> {
@@ -96,32 +101,244 @@ impl<T> List<T> {
         })
     }
 
-    pub fn peek_back(&self) -> Option<Ref<T>> {
+    pub fn peek_back(&self) -> Option<Ref<'_, T>> {
         self.tail
             .as_ref()
             .map(|node| Ref::map(node.borrow(), |node| &node.elem))
     }
 
-    pub fn peek_back_mut(&mut self) -> Option<RefMut<T>> {
+    pub fn peek_back_mut(&mut self) -> Option<RefMut<'_, T>> {
         self.tail
             .as_ref()
             .map(|node| RefMut::map(node.borrow_mut(), |node| &mut node.elem))
     }
 
-    pub fn peek_front_mut(&mut self) -> Option<RefMut<T>> {
+    pub fn peek_front_mut(&mut self) -> Option<RefMut<'_, T>> {
         self.head
             .as_ref()
             .map(|node| RefMut::map(node.borrow_mut(), |node| &mut node.elem))
     }
 
-    pub fn peek_front(&self) -> Option<Ref<T>> {
+    pub fn peek_front(&self) -> Option<Ref<'_, T>> {
         self.head
             .as_ref()
             .map(|head| Ref::map(head.borrow(), |node| &node.elem))
     }
 
-    pub fn into_iter(self) -> IntoIter<T> {
-        IntoIter(self)
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            front: self.head.clone(),
+            back: self.tail.clone(),
+            _list: PhantomData,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            front: self.head.clone(),
+            back: self.tail.clone(),
+            _list: PhantomData,
+        }
+    }
+}
+
+impl<T> List<T> {
+    /// Reverses the list in place by swapping every node's links.
+    pub fn reverse(&mut self) {
+        let mut cur = self.head.clone();
+        while let Some(node) = cur {
+            let mut node = node.borrow_mut();
+            let node = &mut *node;
+            mem::swap(&mut node.prev, &mut node.next);
+            cur = node.prev.clone();
+        }
+        mem::swap(&mut self.head, &mut self.tail);
+    }
+
+    /// Stable merge sort. Runs are merged bottom-up through the `next` links, and the `prev`
+    /// links are fixed up afterwards, so nothing is allocated.
+    pub fn sort_by(&mut self, mut cmp: impl FnMut(&T, &T) -> Ordering) {
+        let len = self.iter().count();
+        let mut width = 1;
+        while width < len {
+            let mut rest = self.head.take();
+            let mut tail: Link<T> = None;
+            while rest.is_some() {
+                let right = cut(&rest, width);
+                let next_rest = cut(&right, width);
+                let (head, run_tail) = merge(rest.take(), right, &mut cmp);
+                match tail {
+                    Some(tail) => tail.borrow_mut().next = head,
+                    None => self.head = head,
+                }
+                tail = run_tail;
+                rest = next_rest;
+            }
+            width *= 2;
+        }
+        self.link_prevs();
+    }
+
+    /// Merges the sorted |other| into this sorted list. Where elements are equal, ours go first.
+    pub fn merge_by(&mut self, mut other: List<T>, mut cmp: impl FnMut(&T, &T) -> Ordering) {
+        other.tail.take();
+        let (head, _) = merge(self.head.take(), other.head.take(), &mut cmp);
+        self.head = head;
+        self.link_prevs();
+    }
+
+    /// Points every `prev` link, and the tail, back along the `next` links.
+    fn link_prevs(&mut self) {
+        let mut prev: Link<T> = None;
+        let mut cur = self.head.clone();
+        while let Some(node) = cur {
+            node.borrow_mut().prev = prev;
+            cur = node.borrow().next.clone();
+            prev = Some(node);
+        }
+        self.tail = prev;
+    }
+}
+
+impl<T: Ord> List<T> {
+    pub fn sort(&mut self) {
+        self.sort_by(T::cmp);
+    }
+
+    pub fn merge(&mut self, other: List<T>) {
+        self.merge_by(other, T::cmp);
+    }
+}
+
+/// Cuts the chain starting at |head| after |n| nodes and returns the rest. Only `next` links are
+/// touched.
+fn cut<T>(head: &Link<T>, n: usize) -> Link<T> {
+    let mut node = head.clone()?;
+    for _ in 1..n {
+        let next = node.borrow().next.clone()?;
+        node = next;
+    }
+    node.borrow_mut().next.take()
+}
+
+/// Merges two sorted chains along their `next` links, taking from |left| first when elements are
+/// equal. Returns the head and tail of the result.
+fn merge<T>(
+    mut left: Link<T>,
+    mut right: Link<T>,
+    cmp: &mut impl FnMut(&T, &T) -> Ordering,
+) -> (Link<T>, Link<T>) {
+    let mut head: Link<T> = None;
+    let mut tail: Link<T> = None;
+    loop {
+        let take_right = match (&left, &right) {
+            (Some(l), Some(r)) => cmp(&r.borrow().elem, &l.borrow().elem) == Ordering::Less,
+            (Some(_), None) => false,
+            (None, Some(_)) => true,
+            (None, None) => break,
+        };
+        let from = if take_right { &mut right } else { &mut left };
+        let node = from.take().unwrap();
+        *from = node.borrow_mut().next.take();
+        match &tail {
+            Some(tail) => tail.borrow_mut().next = Some(node.clone()),
+            None => head = Some(node.clone()),
+        }
+        tail = Some(node);
+    }
+    (head, tail)
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> Clone for List<T> {
+    fn clone(&self) -> Self {
+        let mut list = Self::new();
+        for elem in self {
+            list.push_back(elem.borrow().clone());
+        }
+        list
+    }
+}
+
+impl<T> Extend<T> for List<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.push_back(elem);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T: Debug> Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut list = f.debug_list();
+        for elem in self {
+            list.entry(&*elem.borrow());
+        }
+        list.finish()
+    }
+}
+
+/// Compares |a| and |b| lexicographically with |cmp|, like `Iterator::partial_cmp` would if
+/// elements could be borrowed for longer than their guards.
+fn compare<T>(
+    a: &List<T>,
+    b: &List<T>,
+    mut cmp: impl FnMut(&T, &T) -> Option<Ordering>,
+) -> Option<Ordering> {
+    let (mut a, mut b) = (a.iter(), b.iter());
+    loop {
+        match (a.next(), b.next()) {
+            (None, None) => return Some(Ordering::Equal),
+            (None, Some(_)) => return Some(Ordering::Less),
+            (Some(_), None) => return Some(Ordering::Greater),
+            (Some(x), Some(y)) => match cmp(&x.borrow(), &y.borrow()) {
+                Some(Ordering::Equal) => {}
+                ordering => return ordering,
+            },
+        }
+    }
+}
+
+impl<T: PartialEq> PartialEq for List<T> {
+    fn eq(&self, other: &Self) -> bool {
+        let eq = |x: &T, y: &T| (x == y).then_some(Ordering::Equal);
+        compare(self, other, eq) == Some(Ordering::Equal)
+    }
+}
+
+impl<T: Eq> Eq for List<T> {}
+
+impl<T: PartialOrd> PartialOrd for List<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        compare(self, other, T::partial_cmp)
+    }
+}
+
+impl<T: Ord> Ord for List<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        compare(self, other, |x, y| Some(x.cmp(y))).unwrap()
+    }
+}
+
+impl<T: Hash> Hash for List<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.iter().count().hash(state);
+        for elem in self {
+            elem.borrow().hash(state);
+        }
     }
 }
 
@@ -133,6 +350,15 @@ impl<T> Drop for List<T> {
 
 pub struct IntoIter<T>(List<T>);
 
+impl<T> IntoIterator for List<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter(self)
+    }
+}
+
 impl<T> Iterator for IntoIter<T> {
     type Item = T;
     fn next(&mut self) -> Option<T> {
@@ -146,6 +372,122 @@ impl<T> DoubleEndedIterator for IntoIter<T> {
     }
 }
 
+impl<'a, T> IntoIterator for &'a List<T> {
+    type Item = ElemRef<'a, T>;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut List<T> {
+    type Item = ElemMut<'a, T>;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+/// Steps |front| towards |back|, returning the node it was on. Both are cleared once they meet.
+fn step<T>(front: &mut Link<T>, back: &mut Link<T>, forward: bool) -> Link<T> {
+    let node = front.take()?;
+    if back.as_ref().is_some_and(|back| Rc::ptr_eq(&node, back)) {
+        back.take();
+    } else {
+        let node = node.borrow();
+        *front = if forward {
+            node.next.clone()
+        } else {
+            node.prev.clone()
+        };
+    }
+    Some(node)
+}
+
+// We can't hand out a `Ref` that outlives the iterator's own `Rc` clones, so iteration yields
+// guards that keep their node alive and borrow it on demand. The lifetime keeps the list borrowed
+// for as long as any of them are around, so it can't be popped from under them.
+
+/// An element of a list being iterated over with `iter`.
+pub struct ElemRef<'a, T> {
+    node: Rc<RefCell<Node<T>>>,
+    _list: PhantomData<&'a List<T>>,
+}
+
+impl<T> ElemRef<'_, T> {
+    pub fn borrow(&self) -> Ref<'_, T> {
+        Ref::map(self.node.borrow(), |node| &node.elem)
+    }
+}
+
+/// An element of a list being iterated over with `iter_mut`.
+pub struct ElemMut<'a, T> {
+    node: Rc<RefCell<Node<T>>>,
+    _list: PhantomData<&'a mut List<T>>,
+}
+
+impl<T> ElemMut<'_, T> {
+    pub fn borrow(&self) -> Ref<'_, T> {
+        Ref::map(self.node.borrow(), |node| &node.elem)
+    }
+
+    pub fn borrow_mut(&self) -> RefMut<'_, T> {
+        RefMut::map(self.node.borrow_mut(), |node| &mut node.elem)
+    }
+}
+
+pub struct Iter<'a, T> {
+    front: Link<T>,
+    back: Link<T>,
+    _list: PhantomData<&'a List<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = ElemRef<'a, T>;
+    fn next(&mut self) -> Option<Self::Item> {
+        step(&mut self.front, &mut self.back, true).map(|node| ElemRef {
+            node,
+            _list: PhantomData,
+        })
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        step(&mut self.back, &mut self.front, false).map(|node| ElemRef {
+            node,
+            _list: PhantomData,
+        })
+    }
+}
+
+pub struct IterMut<'a, T> {
+    front: Link<T>,
+    back: Link<T>,
+    _list: PhantomData<&'a mut List<T>>,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = ElemMut<'a, T>;
+    fn next(&mut self) -> Option<Self::Item> {
+        step(&mut self.front, &mut self.back, true).map(|node| ElemMut {
+            node,
+            _list: PhantomData,
+        })
+    }
+}
+
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        step(&mut self.back, &mut self.front, false).map(|node| ElemMut {
+            node,
+            _list: PhantomData,
+        })
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::List;
@@ -235,4 +577,150 @@ mod test {
         assert_eq!(iter.next_back(), None);
         assert_eq!(iter.next(), None);
     }
+
+    #[test]
+    fn iter() {
+        let mut list = List::new();
+        assert!(list.iter().next().is_none());
+        list.push_back(1);
+        list.push_back(2);
+        list.push_back(3);
+
+        let mut iter = list.iter();
+        assert_eq!(*iter.next().unwrap().borrow(), 1);
+        assert_eq!(*iter.next_back().unwrap().borrow(), 3);
+        assert_eq!(*iter.next().unwrap().borrow(), 2);
+        assert!(iter.next().is_none());
+        assert!(iter.next_back().is_none());
+
+        let elems: Vec<_> = list.iter().rev().map(|elem| *elem.borrow()).collect();
+        assert_eq!(elems, vec![3, 2, 1]);
+
+        // Nothing is left holding on to the nodes.
+        assert_eq!(list.pop_front(), Some(1));
+    }
+
+    #[test]
+    fn iter_mut() {
+        let mut list = List::new();
+        list.push_back(1);
+        list.push_back(2);
+        list.push_back(3);
+
+        let elems: Vec<_> = list.iter_mut().collect();
+        for elem in elems.iter() {
+            *elem.borrow_mut() *= 10;
+        }
+        *elems[0].borrow_mut() += *elems[2].borrow();
+        drop(elems);
+
+        let mut iter = list.iter_mut();
+        *iter.next_back().unwrap().borrow_mut() += 1;
+        assert!(iter.next().is_some());
+        assert!(iter.next().is_some());
+        assert!(iter.next_back().is_none());
+
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), vec![40, 20, 31]);
+    }
+
+    #[test]
+    fn traits() {
+        use std::hash::{DefaultHasher, Hash, Hasher};
+
+        let mut list: List<i32> = (1..=3).collect();
+        assert_eq!(&*list.peek_front().unwrap(), &1);
+        assert_eq!(format!("{:?}", list), "[1, 2, 3]");
+
+        list.extend([4, 5]);
+        assert_eq!(list, (1..=5).collect());
+        assert_eq!(list.clone(), list);
+        assert_ne!(list, (1..=4).collect());
+
+        assert!(list < (2..3).collect());
+        assert!(list > (1..=4).collect());
+        assert!(List::new() < list);
+        assert_eq!(
+            List::from_iter([1.0, f64::NAN]).partial_cmp(&List::from_iter([1.0, 2.0])),
+            None
+        );
+
+        for elem in &mut list {
+            *elem.borrow_mut() *= 10;
+        }
+        assert_eq!((&list).into_iter().map(|e| *e.borrow()).sum::<i32>(), 150);
+
+        // Hashed directly: clippy flags a HashSet of these for the RefCells in the nodes.
+        let hash = |list: &List<i32>| {
+            let mut hasher = DefaultHasher::new();
+            list.hash(&mut hasher);
+            hasher.finish()
+        };
+        assert_eq!(hash(&list), hash(&list.clone()));
+        assert_ne!(hash(&list), hash(&List::default()));
+    }
+
+    fn collect(list: &List<u32>) -> Vec<u32> {
+        list.iter().map(|elem| *elem.borrow()).collect()
+    }
+
+    #[test]
+    fn reverse() {
+        let mut list: List<u32> = (1..=4).collect();
+        list.reverse();
+        assert_eq!(collect(&list), vec![4, 3, 2, 1]);
+        assert_eq!(
+            list.iter().rev().map(|e| *e.borrow()).collect::<Vec<_>>(),
+            vec![1, 2, 3, 4]
+        );
+        assert_eq!(list.pop_back(), Some(1));
+        assert_eq!(list.pop_front(), Some(4));
+    }
+
+    #[test]
+    fn sort() {
+        for len in [0, 1, 2, 3, 7, 64, 1001] {
+            let elems: Vec<u32> = (0..len)
+                .map(|i: u32| i.wrapping_mul(2_654_435_761) % 1000)
+                .collect();
+            let mut list: List<u32> = elems.iter().copied().collect();
+            list.sort();
+            let mut expected = elems.clone();
+            expected.sort();
+            assert_eq!(collect(&list), expected);
+
+            // The back links have to agree, and popping checks nothing else holds on to nodes.
+            expected.reverse();
+            let mut popped = vec![];
+            while let Some(elem) = list.pop_back() {
+                popped.push(elem);
+            }
+            assert_eq!(popped, expected);
+        }
+
+        // Equal keys keep their order.
+        let mut list: List<(u32, usize)> = [3, 1, 3, 2, 1, 3].into_iter().zip(0..).collect();
+        list.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            list.into_iter().collect::<Vec<_>>(),
+            vec![(1, 1), (1, 4), (2, 3), (3, 0), (3, 2), (3, 5)]
+        );
+    }
+
+    #[test]
+    fn merge() {
+        let mut list: List<(u32, char)> = [(1, 'a'), (3, 'a'), (8, 'a')].into_iter().collect();
+        list.merge_by([(0, 'x'), (3, 'x')].into_iter().collect(), |a, b| {
+            a.0.cmp(&b.0)
+        });
+        assert_eq!(
+            list.into_iter().rev().collect::<Vec<_>>(),
+            vec![(8, 'a'), (3, 'x'), (3, 'a'), (1, 'a'), (0, 'x')]
+        );
+
+        let mut list = List::new();
+        list.merge((1..=3).collect());
+        list.merge(List::new());
+        assert_eq!(collect(&list), vec![1, 2, 3]);
+        assert_eq!(&*list.peek_back().unwrap(), &3);
+    }
 }
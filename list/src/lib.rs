@@ -2,4 +2,8 @@
 pub mod fourth;
 pub mod second;
 pub mod third;
+pub mod third_arc;
 pub mod fifth;
+pub mod sixth;
+pub mod seventh;
+pub mod arena;
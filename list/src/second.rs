@@ -1,3 +1,7 @@
+use std::cmp::Ordering;
+use std::fmt::{self, Debug};
+use std::hash::{Hash, Hasher};
+
 pub struct List<T> {
     head: Link<T>,
 }
@@ -39,21 +43,96 @@ impl<T> List<T> {
         self.head.as_mut().map(|node| &mut node.elem)
     }
 
-    pub fn into_iter(self) -> IntoIter<T> {
-        IntoIter(self)
-    }
-
-    pub fn iter(&self) -> Iter<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
         Iter {
             next: self.head.as_deref(),
         }
     }
 
-    pub fn iter_mut(&mut self) -> IterMut<T> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
         IterMut {
             next: self.head.as_deref_mut(),
         }
     }
+
+    /// Starts a cursor before the first element.
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            current: None,
+            next: Some(&mut self.head),
+            next_index: 0,
+        }
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> Clone for List<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+/// Appends to the bottom of the stack, so collecting keeps the order: the first element ends up
+/// on top, like it would at the front of a `std::collections::LinkedList`.
+impl<T> Extend<T> for List<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let mut tail = &mut self.head;
+        while let Some(node) = tail {
+            tail = &mut node.next;
+        }
+        for elem in iter {
+            let node = tail.insert(Box::new(Node { elem, next: None }));
+            tail = &mut node.next;
+        }
+    }
+}
+
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T: Debug> Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for List<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for List<T> {}
+
+impl<T: PartialOrd> PartialOrd for List<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<T: Ord> Ord for List<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+impl<T: Hash> Hash for List<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.iter().count().hash(state);
+        for elem in self {
+            elem.hash(state);
+        }
+    }
 }
 
 impl<T> Drop for List<T> {
@@ -66,6 +145,16 @@ impl<T> Drop for List<T> {
 }
 
 pub struct IntoIter<T>(List<T>);
+
+impl<T> IntoIterator for List<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter(self)
+    }
+}
+
 impl<T> Iterator for IntoIter<T> {
     type Item = T;
 
@@ -74,6 +163,24 @@ impl<T> Iterator for IntoIter<T> {
     }
 }
 
+impl<'a, T> IntoIterator for &'a List<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut List<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
 pub struct Iter<'a, T> {
     next: Option<&'a Node<T>>,
 }
@@ -102,6 +209,169 @@ impl<'a, T> Iterator for IterMut<'a, T> {
     }
 }
 
+impl<T> List<T> {
+    /// Reverses the list in place by relinking its nodes.
+    pub fn reverse(&mut self) {
+        let mut reversed = None;
+        let mut cur = self.head.take();
+        while let Some(mut node) = cur {
+            cur = node.next.take();
+            node.next = reversed;
+            reversed = Some(node);
+        }
+        self.head = reversed;
+    }
+
+    /// Stable merge sort. Runs are merged bottom-up by relinking nodes, so nothing is allocated.
+    pub fn sort_by(&mut self, mut cmp: impl FnMut(&T, &T) -> Ordering) {
+        let len = self.iter().count();
+        let mut width = 1;
+        while width < len {
+            let mut rest = self.head.take();
+            let mut tail = &mut self.head;
+            while rest.is_some() {
+                let mut left = rest;
+                let mut right = cut(&mut left, width);
+                rest = cut(&mut right, width);
+                *tail = merge(left, right, &mut cmp);
+                while let Some(node) = tail {
+                    tail = &mut node.next;
+                }
+            }
+            width *= 2;
+        }
+    }
+
+    /// Merges the sorted |other| into this sorted list. Where elements are equal, ours go first.
+    pub fn merge_by(&mut self, mut other: List<T>, mut cmp: impl FnMut(&T, &T) -> Ordering) {
+        self.head = merge(self.head.take(), other.head.take(), &mut cmp);
+    }
+}
+
+impl<T: Ord> List<T> {
+    pub fn sort(&mut self) {
+        self.sort_by(T::cmp);
+    }
+
+    pub fn merge(&mut self, other: List<T>) {
+        self.merge_by(other, T::cmp);
+    }
+}
+
+/// Cuts |link| after |n| nodes and returns the rest.
+fn cut<T>(mut link: &mut Link<T>, n: usize) -> Link<T> {
+    for _ in 0..n {
+        match link {
+            Some(node) => link = &mut node.next,
+            None => return None,
+        }
+    }
+    link.take()
+}
+
+/// Merges two sorted chains, taking from |left| first when elements are equal.
+fn merge<T>(
+    mut left: Link<T>,
+    mut right: Link<T>,
+    cmp: &mut impl FnMut(&T, &T) -> Ordering,
+) -> Link<T> {
+    let mut head = None;
+    let mut tail = &mut head;
+    while let (Some(l), Some(r)) = (&left, &right) {
+        let from = if cmp(&r.elem, &l.elem) == Ordering::Less {
+            &mut right
+        } else {
+            &mut left
+        };
+        let mut node = from.take().unwrap();
+        *from = node.next.take();
+        tail = &mut tail.insert(node).next;
+    }
+    *tail = left.or(right);
+    head
+}
+
+/// A position in a list that edits the link after it. It only walks forward: it starts before the
+/// first element and ends up after the last, where `current` and `index` are None.
+pub struct CursorMut<'a, T> {
+    current: Option<&'a mut T>,
+    // Only None while moving.
+    next: Option<&'a mut Link<T>>,
+    // How many elements come before `next`.
+    next_index: usize,
+}
+
+impl<T> CursorMut<'_, T> {
+    pub fn index(&self) -> Option<usize> {
+        self.current.as_ref().map(|_| self.next_index - 1)
+    }
+
+    /// Moves onto the next element, or past the last one. Once past it, stays there until
+    /// something is inserted after it.
+    pub fn move_next(&mut self) {
+        let link = self.next.take().unwrap();
+        if link.is_none() {
+            self.current = None;
+            self.next = Some(link);
+            return;
+        }
+        let node = link.as_deref_mut().unwrap();
+        self.current = Some(&mut node.elem);
+        self.next = Some(&mut node.next);
+        self.next_index += 1;
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        self.current.as_deref_mut()
+    }
+
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        self.link().as_mut().map(|node| &mut node.elem)
+    }
+
+    fn link(&mut self) -> &mut Link<T> {
+        self.next.as_deref_mut().unwrap()
+    }
+
+    /// Inserts |elem| after the cursor, so it's the next element.
+    pub fn insert_after(&mut self, elem: T) {
+        let link = self.link();
+        *link = Some(Box::new(Node {
+            elem,
+            next: link.take(),
+        }));
+    }
+
+    pub fn remove_next(&mut self) -> Option<T> {
+        let link = self.link();
+        link.take().map(|node| {
+            *link = node.next;
+            node.elem
+        })
+    }
+
+    /// Removes everything after the cursor and returns it.
+    pub fn split_after(&mut self) -> List<T> {
+        List {
+            head: self.link().take(),
+        }
+    }
+
+    /// Moves all of |input| in after the cursor.
+    pub fn splice_after(&mut self, mut input: List<T>) {
+        let Some(mut head) = input.head.take() else {
+            return;
+        };
+        let mut tail = &mut head.next;
+        while let Some(node) = tail {
+            tail = &mut node.next;
+        }
+        let link = self.link();
+        *tail = link.take();
+        *link = Some(head);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -128,6 +398,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::option_map_unit_fn)]
     fn peek() {
         let mut list = List::new();
         assert_eq!(list.peek(), None);
@@ -186,4 +457,158 @@ mod tests {
         assert_eq!(iter.next(), Some(&mut 2));
         assert_eq!(iter.next(), Some(&mut 1));
     }
+
+    #[test]
+    fn traits() {
+        let mut list: List<i32> = (1..=3).collect();
+        assert_eq!(list.peek(), Some(&1));
+        assert_eq!(format!("{:?}", list), "[1, 2, 3]");
+
+        list.extend([4, 5]);
+        assert_eq!(list, (1..=5).collect());
+        assert_eq!(list.clone(), list);
+        assert_ne!(list, (1..=4).collect());
+
+        assert!(list < (2..3).collect());
+        assert!(list > (1..=4).collect());
+        assert!(List::new() < list);
+
+        for elem in &mut list {
+            *elem *= 10;
+        }
+        assert_eq!((&list).into_iter().sum::<i32>(), 150);
+
+        let mut set = std::collections::HashSet::new();
+        assert!(set.insert(list.clone()));
+        assert!(!set.insert(list));
+        assert!(set.insert(List::default()));
+    }
+
+    #[test]
+    fn cursor() {
+        let mut list: List<i32> = (1..=5).collect();
+        let mut cursor = list.cursor_mut();
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.peek_next(), Some(&mut 1));
+
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.index(), Some(1));
+        assert_eq!(cursor.current(), Some(&mut 2));
+        assert_eq!(cursor.remove_next(), Some(3));
+        assert_eq!(cursor.peek_next(), Some(&mut 4));
+        cursor.insert_after(30);
+        *cursor.current().unwrap() = 20;
+
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&mut 30));
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.index(), Some(4));
+        assert_eq!(cursor.peek_next(), None);
+        assert_eq!(cursor.remove_next(), None);
+
+        // Past the end, inserts go on the back.
+        cursor.move_next();
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.current(), None);
+        cursor.insert_after(6);
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&mut 6));
+        assert_eq!(cursor.index(), Some(5));
+        assert_eq!(list, List::from_iter([1, 20, 30, 4, 5, 6]));
+    }
+
+    #[test]
+    fn cursor_split_splice() {
+        let mut list: List<i32> = (1..=5).collect();
+        let mut cursor = list.cursor_mut();
+        cursor.move_next();
+        cursor.move_next();
+        let tail = cursor.split_after();
+        assert_eq!(tail, List::from_iter([3, 4, 5]));
+        assert_eq!(cursor.peek_next(), None);
+
+        cursor.splice_after((10..13).collect());
+        cursor.splice_after(List::new());
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&mut 10));
+        cursor.splice_after(tail);
+        assert_eq!(list, List::from_iter([1, 2, 10, 3, 4, 5, 11, 12]));
+
+        let mut cursor = list.cursor_mut();
+        cursor.splice_after(List::from_iter([-1, 0]));
+        assert_eq!(cursor.split_after().iter().count(), 10);
+        assert!(list.peek().is_none());
+    }
+
+    // Cheap deterministic shuffle for the sorting tests.
+    fn scrambled(len: u32) -> Vec<u32> {
+        (0..len)
+            .map(|i| i.wrapping_mul(2_654_435_761) % 1000)
+            .collect()
+    }
+
+    #[test]
+    fn reverse() {
+        let mut list: List<i32> = (1..=4).collect();
+        list.reverse();
+        assert_eq!(list, List::from_iter([4, 3, 2, 1]));
+
+        let mut list: List<i32> = List::new();
+        list.reverse();
+        assert!(list.peek().is_none());
+    }
+
+    #[test]
+    fn sort() {
+        for len in [0, 1, 2, 3, 7, 64, 1001] {
+            let elems = scrambled(len);
+            let mut list: List<u32> = elems.iter().copied().collect();
+            list.sort();
+            let mut expected = elems.clone();
+            expected.sort();
+            assert_eq!(list.iter().copied().collect::<Vec<_>>(), expected);
+        }
+
+        // Equal keys keep their order.
+        let mut list: List<(u32, usize)> = scrambled(500)
+            .into_iter()
+            .map(|x| x % 10)
+            .enumerate()
+            .map(|(i, x)| (x, i))
+            .collect();
+        list.sort_by(|a, b| a.0.cmp(&b.0));
+        let pairs: Vec<_> = list.iter().collect();
+        assert!(pairs.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn merge() {
+        let mut list: List<(i32, char)> = [(1, 'a'), (3, 'a'), (3, 'b'), (8, 'a')]
+            .into_iter()
+            .collect();
+        list.merge_by(
+            [(0, 'x'), (3, 'x'), (9, 'x')].into_iter().collect(),
+            |a, b| a.0.cmp(&b.0),
+        );
+        let merged: List<_> = [
+            (0, 'x'),
+            (1, 'a'),
+            (3, 'a'),
+            (3, 'b'),
+            (3, 'x'),
+            (8, 'a'),
+            (9, 'x'),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(list, merged);
+
+        let mut list = List::new();
+        list.merge((1..=3).collect());
+        list.merge(List::new());
+        assert_eq!(list, (1..=3).collect());
+    }
 }
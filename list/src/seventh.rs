@@ -0,0 +1,390 @@
+//! A lock-free stack: `second::List` with an atomic head that any thread can push to and pop from.
+//!
+//! Popping a node doesn't mean nobody's looking at it anymore, since another thread may have
+//! loaded the same head just before. So popped nodes are retired rather than freed, and only freed
+//! once no thread has a hazard pointer on them. That also rules out ABA: a node can't be freed and
+//! its address reused while someone still expects it at the head.
+
+#[cfg(loom)]
+use loom::sync::atomic::{AtomicBool, AtomicPtr, AtomicUsize};
+#[cfg(not(loom))]
+use std::sync::atomic::{AtomicBool, AtomicPtr, AtomicUsize};
+
+use std::mem::ManuallyDrop;
+use std::ptr;
+use std::sync::atomic::Ordering::{Acquire, Relaxed, Release, SeqCst};
+
+/// How many retired nodes pile up before we check which of them can be freed.
+const SCAN_THRESHOLD: usize = if cfg!(loom) { 1 } else { 64 };
+
+pub struct Stack<T> {
+    head: AtomicPtr<Node<T>>,
+    hazards: AtomicPtr<HazardRecord>,
+    retired: AtomicPtr<Node<T>>,
+    retired_count: AtomicUsize,
+}
+
+struct Node<T> {
+    // Moved out by whoever pops the node, so freeing the node mustn't drop it.
+    elem: ManuallyDrop<T>,
+    next: *mut Node<T>,
+    // Separate from `next`, which other threads may still be reading once the node's retired.
+    next_retired: *mut Node<T>,
+}
+
+/// One thread's hazard pointer. Records are reused but never freed while the stack's alive.
+struct HazardRecord {
+    ptr: AtomicPtr<()>,
+    active: AtomicBool,
+    next: *mut HazardRecord,
+}
+
+/// A hazard record claimed by the current thread. It's released when this is dropped.
+struct Hazard<'a> {
+    record: &'a HazardRecord,
+}
+
+impl Hazard<'_> {
+    /// Protects |node|, which the caller must then check is still reachable.
+    fn protect<T>(&self, node: *mut Node<T>) {
+        self.record.ptr.store(node.cast(), SeqCst);
+    }
+}
+
+impl Drop for Hazard<'_> {
+    fn drop(&mut self) {
+        self.record.ptr.store(ptr::null_mut(), Release);
+        self.record.active.store(false, Release);
+    }
+}
+
+impl<T> Stack<T> {
+    pub fn new() -> Self {
+        Stack {
+            head: AtomicPtr::new(ptr::null_mut()),
+            hazards: AtomicPtr::new(ptr::null_mut()),
+            retired: AtomicPtr::new(ptr::null_mut()),
+            retired_count: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn push(&self, elem: T) {
+        let node = Box::into_raw(Box::new(Node {
+            elem: ManuallyDrop::new(elem),
+            next: ptr::null_mut(),
+            next_retired: ptr::null_mut(),
+        }));
+        let mut head = self.head.load(Relaxed);
+        loop {
+            // Nobody else can see the node until the exchange succeeds.
+            unsafe { (*node).next = head };
+            match self
+                .head
+                .compare_exchange_weak(head, node, Release, Relaxed)
+            {
+                Ok(_) => return,
+                Err(current) => head = current,
+            }
+        }
+    }
+
+    pub fn pop(&self) -> Option<T> {
+        let hazard = self.hazard();
+        let mut head = self.protect_head(&hazard)?;
+        loop {
+            // The hazard keeps |head| from being freed, even if someone else pops it meanwhile.
+            let next = unsafe { (*head).next };
+            if self
+                .head
+                .compare_exchange(head, next, SeqCst, Relaxed)
+                .is_ok()
+            {
+                drop(hazard);
+                // We popped it, so nobody else will take the element. Peeks may still be reading
+                // it, so it's copied out rather than borrowed mutably.
+                let elem = unsafe { ptr::read(&raw const (*head).elem) };
+                self.retire(head);
+                return Some(ManuallyDrop::into_inner(elem));
+            }
+            head = self.protect_head(&hazard)?;
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.load(Acquire).is_null()
+    }
+
+    /// Loads the head and protects it with |hazard|, or returns None if the stack is empty.
+    fn protect_head(&self, hazard: &Hazard) -> Option<*mut Node<T>> {
+        let mut head = self.head.load(Acquire);
+        loop {
+            if head.is_null() {
+                return None;
+            }
+            hazard.protect(head);
+            // If it's still the head after the hazard's up, nobody can have retired it yet.
+            let current = self.head.load(SeqCst);
+            if current == head {
+                return Some(head);
+            }
+            head = current;
+        }
+    }
+
+    /// Claims a free hazard record, or adds a new one if they're all in use.
+    fn hazard(&self) -> Hazard<'_> {
+        let mut record = self.hazards.load(Acquire);
+        while !record.is_null() {
+            let r = unsafe { &*record };
+            if r.active
+                .compare_exchange(false, true, Acquire, Relaxed)
+                .is_ok()
+            {
+                return Hazard { record: r };
+            }
+            record = r.next;
+        }
+
+        let record = Box::into_raw(Box::new(HazardRecord {
+            ptr: AtomicPtr::new(ptr::null_mut()),
+            active: AtomicBool::new(true),
+            next: ptr::null_mut(),
+        }));
+        let mut head = self.hazards.load(Relaxed);
+        loop {
+            unsafe { (*record).next = head };
+            match self
+                .hazards
+                .compare_exchange_weak(head, record, Release, Relaxed)
+            {
+                Ok(_) => {
+                    return Hazard {
+                        record: unsafe { &*record },
+                    };
+                }
+                Err(current) => head = current,
+            }
+        }
+    }
+
+    fn retire(&self, node: *mut Node<T>) {
+        // Counted before it's pushed, so a scan can't free it before it's been counted.
+        let count = self.retired_count.fetch_add(1, Relaxed) + 1;
+        self.push_retired(node);
+        if count >= SCAN_THRESHOLD {
+            self.scan();
+        }
+    }
+
+    fn push_retired(&self, node: *mut Node<T>) {
+        let mut head = self.retired.load(Relaxed);
+        loop {
+            unsafe { (*node).next_retired = head };
+            match self
+                .retired
+                .compare_exchange_weak(head, node, Release, Relaxed)
+            {
+                Ok(_) => return,
+                Err(current) => head = current,
+            }
+        }
+    }
+
+    /// Frees every retired node no hazard points at, and puts the rest back.
+    fn scan(&self) {
+        let mut node = self.retired.swap(ptr::null_mut(), Acquire);
+        if node.is_null() {
+            return;
+        }
+
+        let mut protected = vec![];
+        let mut record = self.hazards.load(Acquire);
+        while !record.is_null() {
+            let r = unsafe { &*record };
+            let ptr = r.ptr.load(SeqCst);
+            if !ptr.is_null() {
+                protected.push(ptr);
+            }
+            record = r.next;
+        }
+
+        let mut freed = 0;
+        while !node.is_null() {
+            let next = unsafe { (*node).next_retired };
+            if protected.contains(&node.cast()) {
+                self.push_retired(node);
+            } else {
+                // The element was taken when the node was popped; this only frees the node.
+                drop(unsafe { Box::from_raw(node) });
+                freed += 1;
+            }
+            node = next;
+        }
+        self.retired_count.fetch_sub(freed, Relaxed);
+    }
+}
+
+impl<T: Copy> Stack<T> {
+    /// A copy of the top element. Only for `Copy` types: anything else could be in the middle of
+    /// being moved out by a pop.
+    pub fn peek(&self) -> Option<T> {
+        let hazard = self.hazard();
+        let head = self.protect_head(&hazard)?;
+        let elem = unsafe { ptr::read(&raw const (*head).elem) };
+        Some(ManuallyDrop::into_inner(elem))
+    }
+}
+
+impl<T> Default for Stack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for Stack<T> {
+    fn drop(&mut self) {
+        // No other threads are left, so nothing needs protecting.
+        while self.pop().is_some() {}
+
+        let mut node = self.retired.load(Acquire);
+        while !node.is_null() {
+            let boxed = unsafe { Box::from_raw(node) };
+            node = boxed.next_retired;
+        }
+        let mut record = self.hazards.load(Acquire);
+        while !record.is_null() {
+            let boxed = unsafe { Box::from_raw(record) };
+            record = boxed.next;
+        }
+    }
+}
+
+// The raw pointers opt us out of these. Elements move between threads, but are never shared.
+unsafe impl<T: Send> Send for Stack<T> {}
+unsafe impl<T: Send> Sync for Stack<T> {}
+
+#[cfg(all(test, not(loom)))]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn basics() {
+        let stack = Stack::new();
+        assert_eq!(stack.pop(), None);
+        assert!(stack.is_empty());
+
+        stack.push(1);
+        stack.push(2);
+        stack.push(3);
+
+        assert_eq!(stack.peek(), Some(3));
+        assert_eq!(stack.pop(), Some(3));
+        assert_eq!(stack.pop(), Some(2));
+
+        stack.push(4);
+        stack.push(5);
+
+        assert_eq!(stack.pop(), Some(5));
+        assert_eq!(stack.pop(), Some(4));
+        assert_eq!(stack.pop(), Some(1));
+        assert_eq!(stack.pop(), None);
+        assert_eq!(stack.peek(), None);
+    }
+
+    #[test]
+    fn drops_elements() {
+        let counted = Arc::new(());
+        let stack = Stack::new();
+        for _ in 0..200 {
+            stack.push(counted.clone());
+        }
+        for _ in 0..150 {
+            stack.pop();
+        }
+        assert_eq!(Arc::strong_count(&counted), 51);
+        drop(stack);
+        assert_eq!(Arc::strong_count(&counted), 1);
+    }
+
+    #[test]
+    fn work_queue() {
+        const THREADS: usize = 8;
+        const JOBS: usize = 10_000;
+
+        let stack = Arc::new(Stack::new());
+        let workers: Vec<_> = (0..THREADS)
+            .map(|t| {
+                let stack = stack.clone();
+                thread::spawn(move || {
+                    let mut popped = vec![];
+                    for i in 0..JOBS {
+                        stack.push(t * JOBS + i);
+                        if i % 3 != 0 {
+                            popped.extend(stack.pop());
+                        }
+                        let _ = stack.peek();
+                    }
+                    popped
+                })
+            })
+            .collect();
+
+        let mut seen: Vec<usize> = workers
+            .into_iter()
+            .flat_map(|w| w.join().unwrap())
+            .collect();
+        while let Some(job) = stack.pop() {
+            seen.push(job);
+        }
+        // Every job came out exactly once.
+        seen.sort();
+        assert_eq!(seen, (0..THREADS * JOBS).collect::<Vec<_>>());
+    }
+}
+
+// Run with `RUSTFLAGS="--cfg loom" LOOM_MAX_PREEMPTIONS=3 cargo test --release seventh`.
+// Without a preemption bound the retry loops make the search effectively endless.
+#[cfg(all(test, loom))]
+mod loom_tests {
+    use super::*;
+    use loom::sync::Arc;
+    use loom::thread;
+
+    #[test]
+    fn push_pop() {
+        loom::model(|| {
+            let stack = Arc::new(Stack::new());
+            stack.push(1);
+
+            let other = stack.clone();
+            let popper = thread::spawn(move || other.pop());
+            stack.push(2);
+            let mine = stack.pop();
+
+            let mut all = vec![popper.join().unwrap(), mine, stack.pop()];
+            all.sort();
+            assert_eq!(all, vec![None, Some(1), Some(2)]);
+        });
+    }
+
+    #[test]
+    fn concurrent_pops() {
+        loom::model(|| {
+            let stack = Arc::new(Stack::new());
+            stack.push(1);
+            stack.push(2);
+
+            let other = stack.clone();
+            let popper = thread::spawn(move || (other.pop(), other.peek()));
+            let mine = stack.pop();
+            let (theirs, _) = popper.join().unwrap();
+
+            let mut both = vec![mine.unwrap(), theirs.unwrap()];
+            both.sort();
+            assert_eq!(both, vec![1, 2]);
+            assert!(stack.is_empty());
+        });
+    }
+}
@@ -0,0 +1,1216 @@
+use std::cmp::Ordering;
+use std::fmt::{self, Debug};
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr::NonNull;
+
+/// A doubly-linked deque built on raw pointers, with borrowing iterators and cursors.
+///
+/// `NonNull` keeps the list covariant in `T` like `Box` and `Vec`, and the `PhantomData` tells
+/// drop check that we own `T`s.
+pub struct List<T> {
+    front: Link<T>,
+    back: Link<T>,
+    len: usize,
+    _boo: PhantomData<T>,
+}
+
+type Link<T> = Option<NonNull<Node<T>>>;
+
+struct Node<T> {
+    front: Link<T>,
+    back: Link<T>,
+    elem: T,
+}
+
+impl<T> List<T> {
+    pub fn new() -> Self {
+        List {
+            front: None,
+            back: None,
+            len: 0,
+            _boo: PhantomData,
+        }
+    }
+
+    pub fn push_front(&mut self, elem: T) {
+        unsafe {
+            let new = NonNull::new_unchecked(Box::into_raw(Box::new(Node {
+                front: None,
+                back: None,
+                elem,
+            })));
+            if let Some(old) = self.front {
+                (*old.as_ptr()).front = Some(new);
+                (*new.as_ptr()).back = Some(old);
+            } else {
+                self.back = Some(new);
+            }
+            self.front = Some(new);
+            self.len += 1;
+        }
+    }
+
+    pub fn push_back(&mut self, elem: T) {
+        unsafe {
+            let new = NonNull::new_unchecked(Box::into_raw(Box::new(Node {
+                front: None,
+                back: None,
+                elem,
+            })));
+            if let Some(old) = self.back {
+                (*old.as_ptr()).back = Some(new);
+                (*new.as_ptr()).front = Some(old);
+            } else {
+                self.front = Some(new);
+            }
+            self.back = Some(new);
+            self.len += 1;
+        }
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        unsafe {
+            self.front.map(|node| {
+                let boxed_node = Box::from_raw(node.as_ptr());
+                let result = boxed_node.elem;
+
+                self.front = boxed_node.back;
+                if let Some(new) = self.front {
+                    (*new.as_ptr()).front = None;
+                } else {
+                    self.back = None;
+                }
+
+                self.len -= 1;
+                result
+            })
+        }
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        unsafe {
+            self.back.map(|node| {
+                let boxed_node = Box::from_raw(node.as_ptr());
+                let result = boxed_node.elem;
+
+                self.back = boxed_node.front;
+                if let Some(new) = self.back {
+                    (*new.as_ptr()).back = None;
+                } else {
+                    self.front = None;
+                }
+
+                self.len -= 1;
+                result
+            })
+        }
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        unsafe { self.front.map(|node| &(*node.as_ptr()).elem) }
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        unsafe { self.front.map(|node| &mut (*node.as_ptr()).elem) }
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        unsafe { self.back.map(|node| &(*node.as_ptr()).elem) }
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        unsafe { self.back.map(|node| &mut (*node.as_ptr()).elem) }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            front: self.front,
+            back: self.back,
+            len: self.len,
+            _boo: PhantomData,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            front: self.front,
+            back: self.back,
+            len: self.len,
+            _boo: PhantomData,
+        }
+    }
+
+    /// Starts a cursor on the "ghost" element between the back and the front.
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            list: self,
+            cur: None,
+            index: None,
+        }
+    }
+
+    /// Splits the list in two at |at|. Returns everything from |at| on, leaving the rest.
+    pub fn split_off(&mut self, at: usize) -> List<T> {
+        let len = self.len;
+        assert!(at <= len, "cannot split off at {} in a list of {}", at, len);
+        if at == 0 {
+            return mem::take(self);
+        }
+
+        // Walk to the element before |at| from whichever end is closer.
+        let mut cursor = self.cursor_mut();
+        if at - 1 < len / 2 {
+            for _ in 0..at {
+                cursor.move_next();
+            }
+        } else {
+            for _ in 0..len - at + 1 {
+                cursor.move_prev();
+            }
+        }
+        cursor.split_after()
+    }
+
+    /// Moves everything in |other| to the back of this list, leaving |other| empty.
+    pub fn append(&mut self, other: &mut List<T>) {
+        self.cursor_mut().splice_before(mem::take(other));
+    }
+}
+
+impl<T> Drop for List<T> {
+    fn drop(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> Clone for List<T> {
+    fn clone(&self) -> Self {
+        let mut new_list = Self::new();
+        for item in self {
+            new_list.push_back(item.clone());
+        }
+        new_list
+    }
+}
+
+impl<T> Extend<T> for List<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push_back(item);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T: Debug> Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for List<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for List<T> {}
+
+impl<T: PartialOrd> PartialOrd for List<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<T: Ord> Ord for List<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+impl<T: Hash> Hash for List<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+        for item in self {
+            item.hash(state);
+        }
+    }
+}
+
+// The raw pointers opt us out of these, but the list owns its nodes like a Box would.
+unsafe impl<T: Send> Send for List<T> {}
+unsafe impl<T: Sync> Sync for List<T> {}
+
+unsafe impl<T: Sync> Send for Iter<'_, T> {}
+unsafe impl<T: Sync> Sync for Iter<'_, T> {}
+
+unsafe impl<T: Send> Send for IterMut<'_, T> {}
+unsafe impl<T: Sync> Sync for IterMut<'_, T> {}
+
+unsafe impl<T: Send> Send for CursorMut<'_, T> {}
+unsafe impl<T: Sync> Sync for CursorMut<'_, T> {}
+
+pub struct Iter<'a, T> {
+    front: Link<T>,
+    back: Link<T>,
+    len: usize,
+    _boo: PhantomData<&'a T>,
+}
+
+impl<'a, T> IntoIterator for &'a List<T> {
+    type IntoIter = Iter<'a, T>;
+    type Item = &'a T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // `len` rather than the links tells us when the two ends have met.
+        if self.len > 0 {
+            self.front.map(|node| unsafe {
+                self.len -= 1;
+                self.front = (*node.as_ptr()).back;
+                &(*node.as_ptr()).elem
+            })
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.back.map(|node| unsafe {
+                self.len -= 1;
+                self.back = (*node.as_ptr()).front;
+                &(*node.as_ptr()).elem
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+pub struct IterMut<'a, T> {
+    front: Link<T>,
+    back: Link<T>,
+    len: usize,
+    _boo: PhantomData<&'a mut T>,
+}
+
+impl<'a, T> IntoIterator for &'a mut List<T> {
+    type IntoIter = IterMut<'a, T>;
+    type Item = &'a mut T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.front.map(|node| unsafe {
+                self.len -= 1;
+                self.front = (*node.as_ptr()).back;
+                &mut (*node.as_ptr()).elem
+            })
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.back.map(|node| unsafe {
+                self.len -= 1;
+                self.back = (*node.as_ptr()).front;
+                &mut (*node.as_ptr()).elem
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+pub struct IntoIter<T> {
+    list: List<T>,
+}
+
+impl<T> IntoIterator for List<T> {
+    type IntoIter = IntoIter<T>;
+    type Item = T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { list: self }
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {
+    fn len(&self) -> usize {
+        self.list.len
+    }
+}
+
+/// A position in a list that can walk it and edit around itself. Besides the elements, it can be
+/// on a "ghost" element between the back and the front, where `current` and `index` are None.
+pub struct CursorMut<'a, T> {
+    list: &'a mut List<T>,
+    cur: Link<T>,
+    index: Option<usize>,
+}
+
+impl<T> CursorMut<'_, T> {
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    pub fn move_next(&mut self) {
+        if let Some(cur) = self.cur {
+            unsafe {
+                self.cur = (*cur.as_ptr()).back;
+                if self.cur.is_some() {
+                    *self.index.as_mut().unwrap() += 1;
+                } else {
+                    self.index = None;
+                }
+            }
+        } else if !self.list.is_empty() {
+            self.cur = self.list.front;
+            self.index = Some(0)
+        }
+    }
+
+    pub fn move_prev(&mut self) {
+        if let Some(cur) = self.cur {
+            unsafe {
+                self.cur = (*cur.as_ptr()).front;
+                if self.cur.is_some() {
+                    *self.index.as_mut().unwrap() -= 1;
+                } else {
+                    self.index = None;
+                }
+            }
+        } else if !self.list.is_empty() {
+            self.cur = self.list.back;
+            self.index = Some(self.list.len - 1)
+        }
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        unsafe { self.cur.map(|cur| &mut (*cur.as_ptr()).elem) }
+    }
+
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        unsafe {
+            let next = match self.cur {
+                Some(cur) => (*cur.as_ptr()).back,
+                None => self.list.front,
+            };
+            next.map(|next| &mut (*next.as_ptr()).elem)
+        }
+    }
+
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        unsafe {
+            let prev = match self.cur {
+                Some(cur) => (*cur.as_ptr()).front,
+                None => self.list.back,
+            };
+            prev.map(|prev| &mut (*prev.as_ptr()).elem)
+        }
+    }
+
+    /// Removes everything before the cursor and returns it. On the ghost, that's the whole list.
+    pub fn split_before(&mut self) -> List<T> {
+        let Some(cur) = self.cur else {
+            return mem::take(self.list);
+        };
+        unsafe {
+            let old_len = self.list.len;
+            let old_idx = self.index.unwrap();
+            let Some(prev) = (*cur.as_ptr()).front else {
+                return List::new();
+            };
+
+            let new_len = old_len - old_idx;
+            let output_len = old_idx;
+            let output_front = self.list.front;
+            let output_back = Some(prev);
+
+            (*cur.as_ptr()).front = None;
+            (*prev.as_ptr()).back = None;
+
+            self.list.len = new_len;
+            self.list.front = Some(cur);
+            self.index = Some(0);
+
+            List {
+                front: output_front,
+                back: output_back,
+                len: output_len,
+                _boo: PhantomData,
+            }
+        }
+    }
+
+    /// Removes everything after the cursor and returns it. On the ghost, that's the whole list.
+    pub fn split_after(&mut self) -> List<T> {
+        let Some(cur) = self.cur else {
+            return mem::take(self.list);
+        };
+        unsafe {
+            let old_len = self.list.len;
+            let old_idx = self.index.unwrap();
+            let Some(next) = (*cur.as_ptr()).back else {
+                return List::new();
+            };
+
+            let new_len = old_idx + 1;
+            let output_len = old_len - new_len;
+            let output_front = Some(next);
+            let output_back = self.list.back;
+
+            (*cur.as_ptr()).back = None;
+            (*next.as_ptr()).front = None;
+
+            self.list.len = new_len;
+            self.list.back = Some(cur);
+
+            List {
+                front: output_front,
+                back: output_back,
+                len: output_len,
+                _boo: PhantomData,
+            }
+        }
+    }
+
+    /// Moves all of |input| in before the cursor. On the ghost, that's onto the back.
+    pub fn splice_before(&mut self, mut input: List<T>) {
+        unsafe {
+            if input.is_empty() {
+                return;
+            }
+            if let Some(cur) = self.cur {
+                let in_front = input.front.take().unwrap();
+                let in_back = input.back.take().unwrap();
+
+                if let Some(prev) = (*cur.as_ptr()).front {
+                    (*prev.as_ptr()).back = Some(in_front);
+                    (*in_front.as_ptr()).front = Some(prev);
+                } else {
+                    self.list.front = Some(in_front);
+                }
+                (*cur.as_ptr()).front = Some(in_back);
+                (*in_back.as_ptr()).back = Some(cur);
+
+                *self.index.as_mut().unwrap() += input.len;
+            } else if let Some(back) = self.list.back {
+                let in_front = input.front.take().unwrap();
+                let in_back = input.back.take().unwrap();
+
+                (*back.as_ptr()).back = Some(in_front);
+                (*in_front.as_ptr()).front = Some(back);
+                self.list.back = Some(in_back);
+            } else {
+                mem::swap(self.list, &mut input);
+            }
+
+            self.list.len += input.len;
+            // Nothing's left in |input| for its Drop to free.
+            input.len = 0;
+        }
+    }
+
+    /// Moves all of |input| in after the cursor. On the ghost, that's onto the front.
+    pub fn splice_after(&mut self, mut input: List<T>) {
+        unsafe {
+            if input.is_empty() {
+                return;
+            }
+            if let Some(cur) = self.cur {
+                let in_front = input.front.take().unwrap();
+                let in_back = input.back.take().unwrap();
+
+                if let Some(next) = (*cur.as_ptr()).back {
+                    (*next.as_ptr()).front = Some(in_back);
+                    (*in_back.as_ptr()).back = Some(next);
+                } else {
+                    self.list.back = Some(in_back);
+                }
+                (*cur.as_ptr()).back = Some(in_front);
+                (*in_front.as_ptr()).front = Some(cur);
+            } else if let Some(front) = self.list.front {
+                let in_front = input.front.take().unwrap();
+                let in_back = input.back.take().unwrap();
+
+                (*front.as_ptr()).front = Some(in_back);
+                (*in_back.as_ptr()).back = Some(front);
+                self.list.front = Some(in_front);
+            } else {
+                mem::swap(self.list, &mut input);
+            }
+
+            self.list.len += input.len;
+            input.len = 0;
+        }
+    }
+
+    pub fn insert_before(&mut self, elem: T) {
+        let mut input = List::new();
+        input.push_back(elem);
+        self.splice_before(input);
+    }
+
+    pub fn insert_after(&mut self, elem: T) {
+        let mut input = List::new();
+        input.push_back(elem);
+        self.splice_after(input);
+    }
+
+    /// Removes the current element and moves on to the next one. Does nothing on the ghost.
+    pub fn remove_current(&mut self) -> Option<T> {
+        let cur = self.cur?;
+        unsafe {
+            let node = Box::from_raw(cur.as_ptr());
+            match node.front {
+                Some(prev) => (*prev.as_ptr()).back = node.back,
+                None => self.list.front = node.back,
+            }
+            match node.back {
+                Some(next) => (*next.as_ptr()).front = node.front,
+                None => self.list.back = node.front,
+            }
+
+            self.list.len -= 1;
+            self.cur = node.back;
+            if self.cur.is_none() {
+                self.index = None;
+            }
+            Some(node.elem)
+        }
+    }
+}
+
+/// ```compile_fail
+/// use list::sixth::IterMut;
+///
+/// fn iter_mut_covariant<'i, 'a, T>(x: IterMut<'i, &'static T>) -> IterMut<'i, &'a T> { x }
+/// ```
+#[allow(dead_code)]
+fn iter_mut_invariant() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn generate_test() -> List<i32> {
+        list_from(&[0, 1, 2, 3, 4, 5, 6])
+    }
+
+    fn list_from<T: Clone>(v: &[T]) -> List<T> {
+        v.iter().cloned().collect()
+    }
+
+    #[test]
+    fn test_basic_front() {
+        let mut list = List::new();
+
+        // Try to break an empty list
+        assert_eq!(list.len(), 0);
+        assert_eq!(list.pop_front(), None);
+        assert_eq!(list.len(), 0);
+
+        // Try to break a one item list
+        list.push_front(10);
+        assert_eq!(list.len(), 1);
+        assert_eq!(list.pop_front(), Some(10));
+        assert_eq!(list.len(), 0);
+        assert_eq!(list.pop_front(), None);
+        assert_eq!(list.len(), 0);
+
+        // Mess around
+        list.push_front(10);
+        assert_eq!(list.len(), 1);
+        list.push_front(20);
+        assert_eq!(list.len(), 2);
+        list.push_front(30);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.pop_front(), Some(30));
+        assert_eq!(list.len(), 2);
+        list.push_front(40);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.pop_front(), Some(40));
+        assert_eq!(list.len(), 2);
+        assert_eq!(list.pop_front(), Some(20));
+        assert_eq!(list.len(), 1);
+        assert_eq!(list.pop_front(), Some(10));
+        assert_eq!(list.len(), 0);
+        assert_eq!(list.pop_front(), None);
+        assert_eq!(list.len(), 0);
+    }
+
+    #[test]
+    fn test_basic() {
+        let mut m = List::new();
+        assert_eq!(m.pop_front(), None);
+        assert_eq!(m.pop_back(), None);
+        assert_eq!(m.pop_front(), None);
+        m.push_front(1);
+        assert_eq!(m.pop_front(), Some(1));
+        m.push_back(2);
+        m.push_back(3);
+        assert_eq!(m.len(), 2);
+        assert_eq!(m.pop_front(), Some(2));
+        assert_eq!(m.pop_front(), Some(3));
+        assert_eq!(m.len(), 0);
+        assert_eq!(m.pop_front(), None);
+        m.push_back(1);
+        m.push_back(3);
+        m.push_back(5);
+        m.push_back(7);
+        assert_eq!(m.pop_front(), Some(1));
+
+        let mut n = List::new();
+        n.push_front(2);
+        n.push_front(3);
+        {
+            assert_eq!(n.front().unwrap(), &3);
+            let x = n.front_mut().unwrap();
+            assert_eq!(*x, 3);
+            *x = 0;
+        }
+        {
+            assert_eq!(n.back().unwrap(), &2);
+            let y = n.back_mut().unwrap();
+            assert_eq!(*y, 2);
+            *y = 1;
+        }
+        assert_eq!(n.pop_front(), Some(0));
+        assert_eq!(n.pop_front(), Some(1));
+    }
+
+    #[test]
+    fn test_iterator() {
+        let m = generate_test();
+        for (i, elt) in m.iter().enumerate() {
+            assert_eq!(i as i32, *elt);
+        }
+        let mut n = List::new();
+        assert_eq!(n.iter().next(), None);
+        n.push_front(4);
+        let mut it = n.iter();
+        assert_eq!(it.size_hint(), (1, Some(1)));
+        assert_eq!(it.next().unwrap(), &4);
+        assert_eq!(it.size_hint(), (0, Some(0)));
+        assert_eq!(it.next(), None);
+    }
+
+    #[test]
+    fn test_iterator_double_end() {
+        let mut n = List::new();
+        assert_eq!(n.iter().next(), None);
+        n.push_front(4);
+        n.push_front(5);
+        n.push_front(6);
+        let mut it = n.iter();
+        assert_eq!(it.size_hint(), (3, Some(3)));
+        assert_eq!(it.next().unwrap(), &6);
+        assert_eq!(it.size_hint(), (2, Some(2)));
+        assert_eq!(it.next_back().unwrap(), &4);
+        assert_eq!(it.size_hint(), (1, Some(1)));
+        assert_eq!(it.next_back().unwrap(), &5);
+        assert_eq!(it.next_back(), None);
+        assert_eq!(it.next(), None);
+    }
+
+    #[test]
+    fn test_rev_iter() {
+        let m = generate_test();
+        for (i, elt) in m.iter().rev().enumerate() {
+            assert_eq!(6 - i as i32, *elt);
+        }
+        let mut n = List::new();
+        assert_eq!(n.iter().next_back(), None);
+        n.push_front(4);
+        let mut it = n.iter().rev();
+        assert_eq!(it.size_hint(), (1, Some(1)));
+        assert_eq!(it.next().unwrap(), &4);
+        assert_eq!(it.size_hint(), (0, Some(0)));
+        assert_eq!(it.next(), None);
+    }
+
+    #[test]
+    fn test_mut_iter() {
+        let mut m = generate_test();
+        let mut len = m.len();
+        for (i, elt) in m.iter_mut().enumerate() {
+            assert_eq!(i as i32, *elt);
+            len -= 1;
+        }
+        assert_eq!(len, 0);
+        let mut n = List::new();
+        assert!(n.iter_mut().next().is_none());
+        n.push_front(4);
+        n.push_back(5);
+        let mut it = n.iter_mut();
+        assert_eq!(it.size_hint(), (2, Some(2)));
+        assert!(it.next().is_some());
+        assert!(it.next().is_some());
+        assert_eq!(it.size_hint(), (0, Some(0)));
+        assert!(it.next().is_none());
+    }
+
+    #[test]
+    fn test_iterator_mut_double_end() {
+        let mut n = List::new();
+        assert!(n.iter_mut().next_back().is_none());
+        n.push_front(4);
+        n.push_front(5);
+        n.push_front(6);
+        let mut it = n.iter_mut();
+        assert_eq!(it.size_hint(), (3, Some(3)));
+        assert_eq!(*it.next().unwrap(), 6);
+        assert_eq!(it.size_hint(), (2, Some(2)));
+        assert_eq!(*it.next_back().unwrap(), 4);
+        assert_eq!(it.size_hint(), (1, Some(1)));
+        assert_eq!(*it.next_back().unwrap(), 5);
+        assert!(it.next_back().is_none());
+        assert!(it.next().is_none());
+    }
+
+    #[test]
+    fn test_into_iter() {
+        let list = generate_test();
+        let mut iter = list.into_iter();
+        assert_eq!(iter.len(), 7);
+        assert_eq!(iter.next(), Some(0));
+        assert_eq!(iter.next_back(), Some(6));
+        assert_eq!(iter.collect::<Vec<_>>(), vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_eq() {
+        let mut n: List<u8> = list_from(&[]);
+        let mut m = list_from(&[]);
+        assert!(n == m);
+        n.push_front(1);
+        assert!(n != m);
+        m.push_back(1);
+        assert!(n == m);
+
+        let n = list_from(&[2, 3, 4]);
+        let m = list_from(&[1, 2, 3]);
+        assert!(n != m);
+    }
+
+    #[test]
+    fn test_ord() {
+        let n = list_from(&[]);
+        let m = list_from(&[1, 2, 3]);
+        assert!(n < m);
+        assert!(m > n);
+        assert!(n <= n);
+        assert!(n >= n);
+    }
+
+    #[test]
+    fn test_ord_nan() {
+        let nan = f64::NAN;
+        let n = list_from(&[nan]);
+        let m = list_from(&[nan]);
+        assert_eq!(n.partial_cmp(&m), None);
+
+        let one = list_from(&[1.0f64]);
+        assert_eq!(n.partial_cmp(&one), None);
+
+        let u = list_from(&[1.0f64, 2.0, nan]);
+        let v = list_from(&[1.0f64, 2.0, 3.0]);
+        assert_eq!(u.partial_cmp(&v), None);
+        assert!(u != v);
+    }
+
+    #[test]
+    fn test_debug() {
+        let list: List<i32> = (0..10).collect();
+        assert_eq!(format!("{:?}", list), "[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]");
+
+        let list: List<&str> = ["just", "one", "test", "more"].into_iter().collect();
+        assert_eq!(format!("{:?}", list), r#"["just", "one", "test", "more"]"#);
+    }
+
+    #[test]
+    fn test_hashmap() {
+        // Check that HashMap works with this as a key
+
+        let list1: List<i32> = (0..10).collect();
+        let list2: List<i32> = (1..11).collect();
+        let mut map = std::collections::HashMap::new();
+
+        assert_eq!(map.insert(list1.clone(), "list1"), None);
+        assert_eq!(map.insert(list2.clone(), "list2"), None);
+
+        assert_eq!(map.len(), 2);
+
+        assert_eq!(map.get(&list1), Some(&"list1"));
+        assert_eq!(map.get(&list2), Some(&"list2"));
+
+        assert_eq!(map.remove(&list1), Some("list1"));
+        assert_eq!(map.remove(&list2), Some("list2"));
+
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn test_cursor_move_peek() {
+        let mut m: List<u32> = List::new();
+        m.extend([1, 2, 3, 4, 5, 6]);
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&mut 1));
+        assert_eq!(cursor.peek_next(), Some(&mut 2));
+        assert_eq!(cursor.peek_prev(), None);
+        assert_eq!(cursor.index(), Some(0));
+        cursor.move_prev();
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.peek_next(), Some(&mut 1));
+        assert_eq!(cursor.peek_prev(), Some(&mut 6));
+        assert_eq!(cursor.index(), None);
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&mut 2));
+        assert_eq!(cursor.peek_next(), Some(&mut 3));
+        assert_eq!(cursor.peek_prev(), Some(&mut 1));
+        assert_eq!(cursor.index(), Some(1));
+
+        let mut cursor = m.cursor_mut();
+        cursor.move_prev();
+        assert_eq!(cursor.current(), Some(&mut 6));
+        assert_eq!(cursor.peek_next(), None);
+        assert_eq!(cursor.peek_prev(), Some(&mut 5));
+        assert_eq!(cursor.index(), Some(5));
+        cursor.move_next();
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.peek_next(), Some(&mut 1));
+        assert_eq!(cursor.peek_prev(), Some(&mut 6));
+        assert_eq!(cursor.index(), None);
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(cursor.current(), Some(&mut 5));
+        assert_eq!(cursor.peek_next(), Some(&mut 6));
+        assert_eq!(cursor.peek_prev(), Some(&mut 4));
+        assert_eq!(cursor.index(), Some(4));
+    }
+
+    #[test]
+    fn test_cursor_mut_insert() {
+        let mut m: List<u32> = List::new();
+        m.extend([1, 2, 3, 4, 5, 6]);
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        cursor.splice_before(Some(7).into_iter().collect());
+        cursor.splice_after(Some(8).into_iter().collect());
+        assert_eq!(
+            m.iter().cloned().collect::<Vec<_>>(),
+            &[7, 1, 8, 2, 3, 4, 5, 6]
+        );
+
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        cursor.move_prev();
+        cursor.splice_before(Some(9).into_iter().collect());
+        cursor.splice_after(Some(10).into_iter().collect());
+        assert_eq!(
+            m.iter().cloned().collect::<Vec<_>>(),
+            &[10, 7, 1, 8, 2, 3, 4, 5, 6, 9]
+        );
+
+        let mut cursor = m.cursor_mut();
+        cursor.move_prev();
+        assert_eq!(cursor.current(), Some(&mut 9));
+        let mut p: List<u32> = List::new();
+        p.extend([100, 101, 102, 103]);
+        let mut q: List<u32> = List::new();
+        q.extend([200, 201, 202, 203]);
+        cursor.splice_after(p);
+        cursor.splice_before(q);
+        assert_eq!(cursor.index(), Some(13));
+        assert_eq!(
+            m.iter().cloned().collect::<Vec<_>>(),
+            &[
+                10, 7, 1, 8, 2, 3, 4, 5, 6, 200, 201, 202, 203, 9, 100, 101, 102, 103
+            ]
+        );
+
+        let mut cursor = m.cursor_mut();
+        cursor.move_prev();
+        let tmp = cursor.split_before();
+        assert_eq!(m.into_iter().collect::<Vec<_>>(), &[103]);
+        let mut m = tmp;
+        assert_eq!(
+            m.iter().cloned().collect::<Vec<_>>(),
+            &[
+                10, 7, 1, 8, 2, 3, 4, 5, 6, 200, 201, 202, 203, 9, 100, 101, 102
+            ]
+        );
+
+        let mut cursor = m.cursor_mut();
+        for _ in 0..5 {
+            cursor.move_next();
+        }
+        let tmp = cursor.split_after();
+        assert_eq!(cursor.index(), Some(4));
+        assert_eq!(
+            tmp.into_iter().collect::<Vec<_>>(),
+            &[3, 4, 5, 6, 200, 201, 202, 203, 9, 100, 101, 102]
+        );
+        assert_eq!(m.into_iter().collect::<Vec<_>>(), &[10, 7, 1, 8, 2]);
+    }
+
+    #[test]
+    fn test_cursor_insert_remove() {
+        let mut m: List<u32> = (1..=4).collect();
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        cursor.move_next();
+        cursor.insert_before(10);
+        cursor.insert_after(20);
+        assert_eq!(cursor.index(), Some(2));
+        assert_eq!(cursor.current(), Some(&mut 2));
+
+        assert_eq!(cursor.remove_current(), Some(2));
+        assert_eq!(cursor.current(), Some(&mut 20));
+        assert_eq!(cursor.index(), Some(2));
+
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(cursor.current(), Some(&mut 1));
+        assert_eq!(cursor.remove_current(), Some(1));
+        assert_eq!(cursor.index(), Some(0));
+
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(cursor.remove_current(), Some(4));
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.remove_current(), None);
+        cursor.insert_after(0);
+        assert_eq!(m.iter().copied().collect::<Vec<_>>(), &[0, 10, 20, 3]);
+        assert_eq!(m.len(), 4);
+        assert_eq!(m.back(), Some(&3));
+    }
+
+    #[test]
+    fn test_split_off_append() {
+        for at in 0..=7 {
+            let mut m = generate_test();
+            let tail = m.split_off(at);
+            assert_eq!(m.len(), at);
+            assert_eq!(tail.len(), 7 - at);
+            assert_eq!(
+                m.iter().copied().collect::<Vec<_>>(),
+                (0..at as i32).collect::<Vec<_>>()
+            );
+            assert_eq!(
+                tail.iter().rev().copied().collect::<Vec<_>>(),
+                (at as i32..7).rev().collect::<Vec<_>>()
+            );
+
+            let mut tail = tail;
+            m.append(&mut tail);
+            assert!(tail.is_empty());
+            assert_eq!(m, generate_test());
+        }
+
+        let mut empty = List::new();
+        let mut m = generate_test();
+        empty.append(&mut m);
+        assert_eq!(empty, generate_test());
+        assert_eq!(m.back(), None);
+    }
+
+    #[test]
+    fn test_split_at_ends() {
+        // Nothing before the first element or after the last, so both halves stay usable.
+        let mut m = generate_test();
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        let mut front = cursor.split_before();
+        assert!(front.is_empty());
+        front.push_back(-1);
+        front.push_front(-2);
+        assert_eq!(front.pop_back(), Some(-1));
+        assert_eq!(front.pop_front(), Some(-2));
+        assert_eq!(front.pop_front(), None);
+
+        let mut cursor = m.cursor_mut();
+        cursor.move_prev();
+        let mut back = cursor.split_after();
+        assert!(back.is_empty());
+        back.push_front(7);
+        assert_eq!(back.pop_back(), Some(7));
+        assert_eq!(back.pop_back(), None);
+        assert_eq!(m, generate_test());
+
+        let mut all = m.split_off(0);
+        assert!(m.is_empty());
+        m.push_back(10);
+        assert_eq!(m.pop_front(), Some(10));
+        all.push_back(7);
+        assert_eq!(all.pop_front(), Some(0));
+        assert_eq!(all.pop_back(), Some(7));
+        assert_eq!(all.len(), 6);
+
+        let mut none = all.split_off(6);
+        assert!(none.is_empty());
+        none.push_back(8);
+        assert_eq!(none.pop_front(), Some(8));
+        assert_eq!(none.pop_back(), None);
+        all.push_back(7);
+        assert_eq!(all.pop_back(), Some(7));
+        assert_eq!(all.pop_back(), Some(6));
+        assert_eq!(all.iter().copied().collect::<Vec<_>>(), &[1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_split_off_past_end() {
+        generate_test().split_off(8);
+    }
+
+    // Interleaves iterators, cursors and splices so Miri can check the links stay valid.
+    #[test]
+    fn miri_food() {
+        let mut list: List<i32> = (0..6).collect();
+        for elem in list.iter_mut().rev() {
+            *elem *= 10;
+        }
+
+        let mut cursor = list.cursor_mut();
+        cursor.move_next();
+        cursor.move_next();
+        cursor.insert_before(5);
+        let mut tail = cursor.split_after();
+        assert_eq!(cursor.remove_current(), Some(10));
+        cursor.move_prev();
+        *cursor.current().unwrap() += 1;
+        cursor.splice_after(tail.split_off(2));
+
+        list.append(&mut tail);
+        let mut iter = list.iter();
+        assert_eq!(iter.next_back(), Some(&30));
+        assert_eq!(iter.next(), Some(&0));
+        assert_eq!(iter.collect::<Vec<_>>(), vec![&6, &40, &50, &20]);
+
+        assert_eq!(list.pop_back(), Some(30));
+        let mut iter = list.into_iter();
+        assert_eq!(iter.next(), Some(0));
+        // Drop the rest half-consumed.
+    }
+
+    #[allow(dead_code)]
+    fn assert_properties() {
+        fn is_send<T: Send>() {}
+        fn is_sync<T: Sync>() {}
+
+        is_send::<List<i32>>();
+        is_sync::<List<i32>>();
+
+        is_send::<IntoIter<i32>>();
+        is_sync::<IntoIter<i32>>();
+
+        is_send::<Iter<i32>>();
+        is_sync::<Iter<i32>>();
+
+        is_send::<IterMut<i32>>();
+        is_sync::<IterMut<i32>>();
+
+        is_send::<CursorMut<i32>>();
+        is_sync::<CursorMut<i32>>();
+
+        fn linked_list_covariant<'a, T>(x: List<&'static T>) -> List<&'a T> {
+            x
+        }
+        fn iter_covariant<'i, 'a, T>(x: Iter<'i, &'static T>) -> Iter<'i, &'a T> {
+            x
+        }
+        fn into_iter_covariant<'a, T>(x: IntoIter<&'static T>) -> IntoIter<&'a T> {
+            x
+        }
+    }
+}
@@ -1,7 +1,12 @@
+use std::cmp::Ordering;
+use std::fmt::{self, Debug};
+use std::hash::{Hash, Hasher};
+use std::mem;
 use std::rc::Rc;
 
 pub struct List<T> {
     head: Link<T>,
+    len: usize,
 }
 
 type Link<T> = Option<Rc<Node<T>>>;
@@ -13,7 +18,7 @@ struct Node<T> {
 
 impl<T> List<T> {
     pub fn new() -> Self {
-        List { head: None }
+        List { head: None, len: 0 }
     }
 
     pub fn prepend(&self, elem: T) -> List<T> {
@@ -21,12 +26,16 @@ impl<T> List<T> {
             elem,
             next: self.head.clone(),
         });
-        List { head: Some(head) }
+        List {
+            head: Some(head),
+            len: self.len + 1,
+        }
     }
 
     pub fn tail(&self) -> List<T> {
         List {
             head: self.head.as_ref().and_then(|node| node.next.clone()),
+            len: self.len.saturating_sub(1),
         }
     }
 
@@ -39,6 +48,160 @@ impl<T> List<T> {
             next: self.head.as_deref(),
         }
     }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    pub fn nth(&self, n: usize) -> Option<&T> {
+        self.iter().nth(n)
+    }
+
+    /// The list without its first |n| elements. Shares all of them with this one.
+    pub fn drop(&self, n: usize) -> List<T> {
+        let mut head = self.head.as_ref();
+        for _ in 0..n.min(self.len) {
+            head = head.and_then(|node| node.next.as_ref());
+        }
+        List {
+            head: head.cloned(),
+            len: self.len.saturating_sub(n),
+        }
+    }
+
+    /// Builds a list of new nodes for |elems| that ends by sharing |rest|.
+    fn build(elems: impl IntoIterator<Item = T>, rest: &List<T>) -> List<T> {
+        let mut list = List::new();
+        let mut tail = &mut list.head;
+        for elem in elems {
+            let node = tail.insert(Rc::new(Node { elem, next: None }));
+            // Nothing else can see the node yet.
+            tail = &mut Rc::get_mut(node).unwrap().next;
+            list.len += 1;
+        }
+        *tail = rest.head.clone();
+        list.len += rest.len;
+        list
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> List<U> {
+        List::build(self.iter().map(f), &List::new())
+    }
+}
+
+impl<T: Clone> List<T> {
+    /// The first |n| elements. These have to be copied, unless that's the whole list.
+    pub fn take(&self, n: usize) -> List<T> {
+        if n >= self.len {
+            return self.clone();
+        }
+        List::build(self.iter().take(n).cloned(), &List::new())
+    }
+
+    pub fn reverse(&self) -> List<T> {
+        self.iter()
+            .fold(List::new(), |list, elem| list.prepend(elem.clone()))
+    }
+
+    /// This list followed by |other|. Only this list's nodes are copied; |other| is shared.
+    pub fn append(&self, other: &List<T>) -> List<T> {
+        if other.is_empty() {
+            return self.clone();
+        }
+        List::build(self.iter().cloned(), other)
+    }
+
+    /// The elements that match |pred|. Everything after the last element that doesn't is shared.
+    pub fn filter(&self, mut pred: impl FnMut(&T) -> bool) -> List<T> {
+        let keep: Vec<bool> = self.iter().map(&mut pred).collect();
+        let Some(last_dropped) = keep.iter().rposition(|keep| !keep) else {
+            return self.clone();
+        };
+        let kept = self
+            .iter()
+            .zip(keep)
+            .take(last_dropped)
+            .filter(|(_, keep)| *keep)
+            .map(|(elem, _)| elem.clone());
+        List::build(kept, &self.drop(last_dropped + 1))
+    }
+
+    pub fn zip<U: Clone>(&self, other: &List<U>) -> List<(T, U)> {
+        List::build(
+            self.iter().cloned().zip(other.iter().cloned()),
+            &List::new(),
+        )
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Shares every node with the original, like `prepend` and `tail` do.
+impl<T> Clone for List<T> {
+    fn clone(&self) -> Self {
+        List {
+            head: self.head.clone(),
+            len: self.len,
+        }
+    }
+}
+
+/// Nodes may be shared with other lists, so there's no appending to them in place: the existing
+/// elements are copied into a new list ahead of the new ones.
+impl<T: Clone> Extend<T> for List<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let old = mem::take(self);
+        *self = old.iter().cloned().chain(iter).collect();
+    }
+}
+
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        List::build(iter, &List::new())
+    }
+}
+
+impl<T: Debug> Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for List<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for List<T> {}
+
+impl<T: PartialOrd> PartialOrd for List<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<T: Ord> Ord for List<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+impl<T: Hash> Hash for List<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len.hash(state);
+        for elem in self {
+            elem.hash(state);
+        }
+    }
 }
 
 impl<T> Drop for List<T> {
@@ -54,6 +217,15 @@ impl<T> Drop for List<T> {
     }
 }
 
+impl<'a, T> IntoIterator for &'a List<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
 pub struct Iter<'a, T> {
     next: Option<&'a Node<T>>,
 }
@@ -103,4 +275,102 @@ mod tests {
         assert_eq!(iter.next(), Some(&2));
         assert_eq!(iter.next(), Some(&1));
     }
+
+    #[test]
+    fn traits() {
+        let list: List<i32> = (1..=3).collect();
+        assert_eq!(list.head(), Some(&1));
+        assert_eq!(format!("{:?}", list), "[1, 2, 3]");
+
+        let shared = list.clone();
+        assert!(Rc::ptr_eq(
+            shared.head.as_ref().unwrap(),
+            list.head.as_ref().unwrap()
+        ));
+
+        let mut extended = list.tail();
+        extended.extend([4, 5]);
+        assert_eq!(extended, (2..=5).collect());
+        // The list it was split from is untouched.
+        assert_eq!(list, shared);
+        assert_eq!(list.iter().count(), 3);
+
+        assert!(list < extended);
+        assert!(list < list.tail().tail());
+        assert!(list > List::new());
+        assert_eq!(list.cmp(&list.prepend(0).tail()), Ordering::Equal);
+
+        let mut set = std::collections::HashSet::new();
+        assert!(set.insert(list));
+        assert!(!set.insert(shared));
+        assert!(set.insert(List::default()));
+    }
+
+    fn shares<T>(a: &List<T>, b: &List<T>) -> bool {
+        match (&a.head, &b.head) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn len_nth_drop() {
+        let list: List<i32> = (1..=4).collect();
+        assert_eq!(list.len(), 4);
+        assert_eq!(list.prepend(0).len(), 5);
+        assert_eq!(list.tail().len(), 3);
+        assert_eq!(List::<i32>::new().tail().len(), 0);
+        assert_eq!(list.nth(2), Some(&3));
+        assert_eq!(list.nth(4), None);
+
+        assert!(shares(&list.drop(0), &list));
+        assert!(shares(&list.drop(2), &list.tail().tail()));
+        assert_eq!(list.drop(2), (3..=4).collect());
+        assert_eq!(list.drop(2).len(), 2);
+        assert!(list.drop(9).is_empty());
+        assert_eq!(list.drop(9).len(), 0);
+    }
+
+    #[test]
+    fn take_reverse_map_zip() {
+        let list: List<i32> = (1..=4).collect();
+        let taken = list.take(2);
+        assert_eq!(taken, (1..=2).collect());
+        assert_eq!(taken.len(), 2);
+        assert!(shares(&list.take(4), &list));
+
+        assert_eq!(list.reverse(), (1..=4).rev().collect());
+        assert_eq!(list.map(|x| x * 10), List::from_iter([10, 20, 30, 40]));
+        let zipped = list.zip(&list.drop(1).map(|x| x.to_string()));
+        assert_eq!(zipped.len(), 3);
+        assert_eq!(zipped.nth(2), Some(&(3, "4".to_string())));
+    }
+
+    #[test]
+    fn append_copies_left_spine() {
+        let left: List<i32> = (1..=2).collect();
+        let right: List<i32> = (3..=5).collect();
+        let both = left.append(&right);
+        assert_eq!(both, (1..=5).collect());
+        assert_eq!(both.len(), 5);
+        assert!(shares(&both.drop(2), &right));
+        assert!(!shares(&both, &left));
+        assert!(shares(&left.append(&List::new()), &left));
+        assert!(shares(&List::new().append(&right), &right));
+    }
+
+    #[test]
+    fn filter_shares_suffix() {
+        let list: List<i32> = [1, 2, 3, 4, 6, 8].into_iter().collect();
+        let even = list.filter(|x| x % 2 == 0);
+        assert_eq!(even, List::from_iter([2, 4, 6, 8]));
+        assert_eq!(even.len(), 4);
+        // Everything from the 4 on passes, so that part isn't copied.
+        assert!(shares(&even.drop(1), &list.drop(3)));
+
+        assert!(shares(&list.filter(|_| true), &list));
+        assert!(list.filter(|_| false).is_empty());
+        assert_eq!(list.filter(|x| *x < 3), List::from_iter([1, 2]));
+    }
 }
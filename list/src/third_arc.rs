@@ -0,0 +1,424 @@
+use std::cmp::Ordering;
+use std::fmt::{self, Debug};
+use std::hash::{Hash, Hasher};
+use std::mem;
+use std::sync::Arc;
+
+/// `third::List` on `Arc`, so lists can be shared between threads. Everything else is the same.
+pub struct List<T> {
+    head: Link<T>,
+    len: usize,
+}
+
+type Link<T> = Option<Arc<Node<T>>>;
+
+struct Node<T> {
+    elem: T,
+    next: Link<T>,
+}
+
+impl<T> List<T> {
+    pub fn new() -> Self {
+        List { head: None, len: 0 }
+    }
+
+    pub fn prepend(&self, elem: T) -> List<T> {
+        let head = Arc::new(Node {
+            elem,
+            next: self.head.clone(),
+        });
+        List {
+            head: Some(head),
+            len: self.len + 1,
+        }
+    }
+
+    pub fn tail(&self) -> List<T> {
+        List {
+            head: self.head.as_ref().and_then(|node| node.next.clone()),
+            len: self.len.saturating_sub(1),
+        }
+    }
+
+    pub fn head(&self) -> Option<&T> {
+        self.head.as_ref().map(|node| &node.elem)
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head.as_deref(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    pub fn nth(&self, n: usize) -> Option<&T> {
+        self.iter().nth(n)
+    }
+
+    /// The list without its first |n| elements. Shares all of them with this one.
+    pub fn drop(&self, n: usize) -> List<T> {
+        let mut head = self.head.as_ref();
+        for _ in 0..n.min(self.len) {
+            head = head.and_then(|node| node.next.as_ref());
+        }
+        List {
+            head: head.cloned(),
+            len: self.len.saturating_sub(n),
+        }
+    }
+
+    /// Builds a list of new nodes for |elems| that ends by sharing |rest|.
+    fn build(elems: impl IntoIterator<Item = T>, rest: &List<T>) -> List<T> {
+        let mut list = List::new();
+        let mut tail = &mut list.head;
+        for elem in elems {
+            let node = tail.insert(Arc::new(Node { elem, next: None }));
+            // Nothing else can see the node yet.
+            tail = &mut Arc::get_mut(node).unwrap().next;
+            list.len += 1;
+        }
+        *tail = rest.head.clone();
+        list.len += rest.len;
+        list
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> List<U> {
+        List::build(self.iter().map(f), &List::new())
+    }
+}
+
+impl<T: Clone> List<T> {
+    /// The first |n| elements. These have to be copied, unless that's the whole list.
+    pub fn take(&self, n: usize) -> List<T> {
+        if n >= self.len {
+            return self.clone();
+        }
+        List::build(self.iter().take(n).cloned(), &List::new())
+    }
+
+    pub fn reverse(&self) -> List<T> {
+        self.iter()
+            .fold(List::new(), |list, elem| list.prepend(elem.clone()))
+    }
+
+    /// This list followed by |other|. Only this list's nodes are copied; |other| is shared.
+    pub fn append(&self, other: &List<T>) -> List<T> {
+        if other.is_empty() {
+            return self.clone();
+        }
+        List::build(self.iter().cloned(), other)
+    }
+
+    /// The elements that match |pred|. Everything after the last element that doesn't is shared.
+    pub fn filter(&self, mut pred: impl FnMut(&T) -> bool) -> List<T> {
+        let keep: Vec<bool> = self.iter().map(&mut pred).collect();
+        let Some(last_dropped) = keep.iter().rposition(|keep| !keep) else {
+            return self.clone();
+        };
+        let kept = self
+            .iter()
+            .zip(keep)
+            .take(last_dropped)
+            .filter(|(_, keep)| *keep)
+            .map(|(elem, _)| elem.clone());
+        List::build(kept, &self.drop(last_dropped + 1))
+    }
+
+    pub fn zip<U: Clone>(&self, other: &List<U>) -> List<(T, U)> {
+        List::build(
+            self.iter().cloned().zip(other.iter().cloned()),
+            &List::new(),
+        )
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Shares every node with the original, like `prepend` and `tail` do.
+impl<T> Clone for List<T> {
+    fn clone(&self) -> Self {
+        List {
+            head: self.head.clone(),
+            len: self.len,
+        }
+    }
+}
+
+/// Nodes may be shared with other lists, so there's no appending to them in place: the existing
+/// elements are copied into a new list ahead of the new ones.
+impl<T: Clone> Extend<T> for List<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let old = mem::take(self);
+        *self = old.iter().cloned().chain(iter).collect();
+    }
+}
+
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        List::build(iter, &List::new())
+    }
+}
+
+impl<T: Debug> Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for List<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for List<T> {}
+
+impl<T: PartialOrd> PartialOrd for List<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<T: Ord> Ord for List<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+impl<T: Hash> Hash for List<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len.hash(state);
+        for elem in self {
+            elem.hash(state);
+        }
+    }
+}
+
+impl<T> Drop for List<T> {
+    fn drop(&mut self) {
+        let mut head = self.head.take();
+        // Like `try_unwrap`, but if two threads drop the last two handles at once, exactly one
+        // gets the node. With `try_unwrap` both could fail and the node would be dropped
+        // recursively, along with everything after it.
+        while let Some(node) = head {
+            if let Some(mut node) = Arc::into_inner(node) {
+                head = node.next.take();
+            } else {
+                break;
+            }
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a List<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+pub struct Iter<'a, T> {
+    next: Option<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|node| {
+            self.next = node.next.as_deref();
+            &node.elem
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn basics() {
+        let list = List::new();
+        assert_eq!(list.head(), None);
+
+        let list = list.prepend(1).prepend(2).prepend(3);
+        assert_eq!(list.head(), Some(&3));
+
+        let list = list.tail();
+        assert_eq!(list.head(), Some(&2));
+
+        let list = list.tail();
+        assert_eq!(list.head(), Some(&1));
+
+        let list = list.tail();
+        assert_eq!(list.head(), None);
+
+        // Make sure empty tail works
+        let list = list.tail();
+        assert_eq!(list.head(), None);
+    }
+
+    #[test]
+    fn iter() {
+        let list = List::new().prepend(1).prepend(2).prepend(3);
+
+        let mut iter = list.iter();
+        assert_eq!(iter.next(), Some(&3));
+        assert_eq!(iter.next(), Some(&2));
+        assert_eq!(iter.next(), Some(&1));
+    }
+
+    #[test]
+    fn traits() {
+        let list: List<i32> = (1..=3).collect();
+        assert_eq!(list.head(), Some(&1));
+        assert_eq!(format!("{:?}", list), "[1, 2, 3]");
+
+        let shared = list.clone();
+        assert!(Arc::ptr_eq(
+            shared.head.as_ref().unwrap(),
+            list.head.as_ref().unwrap()
+        ));
+
+        let mut extended = list.tail();
+        extended.extend([4, 5]);
+        assert_eq!(extended, (2..=5).collect());
+        // The list it was split from is untouched.
+        assert_eq!(list, shared);
+        assert_eq!(list.iter().count(), 3);
+
+        assert!(list < extended);
+        assert!(list < list.tail().tail());
+        assert!(list > List::new());
+        assert_eq!(list.cmp(&list.prepend(0).tail()), Ordering::Equal);
+
+        let mut set = std::collections::HashSet::new();
+        assert!(set.insert(list));
+        assert!(!set.insert(shared));
+        assert!(set.insert(List::default()));
+    }
+
+    fn shares<T>(a: &List<T>, b: &List<T>) -> bool {
+        match (&a.head, &b.head) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn len_nth_drop() {
+        let list: List<i32> = (1..=4).collect();
+        assert_eq!(list.len(), 4);
+        assert_eq!(list.prepend(0).len(), 5);
+        assert_eq!(list.tail().len(), 3);
+        assert_eq!(List::<i32>::new().tail().len(), 0);
+        assert_eq!(list.nth(2), Some(&3));
+        assert_eq!(list.nth(4), None);
+
+        assert!(shares(&list.drop(0), &list));
+        assert!(shares(&list.drop(2), &list.tail().tail()));
+        assert_eq!(list.drop(2), (3..=4).collect());
+        assert_eq!(list.drop(2).len(), 2);
+        assert!(list.drop(9).is_empty());
+        assert_eq!(list.drop(9).len(), 0);
+    }
+
+    #[test]
+    fn take_reverse_map_zip() {
+        let list: List<i32> = (1..=4).collect();
+        let taken = list.take(2);
+        assert_eq!(taken, (1..=2).collect());
+        assert_eq!(taken.len(), 2);
+        assert!(shares(&list.take(4), &list));
+
+        assert_eq!(list.reverse(), (1..=4).rev().collect());
+        assert_eq!(list.map(|x| x * 10), List::from_iter([10, 20, 30, 40]));
+        let zipped = list.zip(&list.drop(1).map(|x| x.to_string()));
+        assert_eq!(zipped.len(), 3);
+        assert_eq!(zipped.nth(2), Some(&(3, "4".to_string())));
+    }
+
+    #[test]
+    fn append_copies_left_spine() {
+        let left: List<i32> = (1..=2).collect();
+        let right: List<i32> = (3..=5).collect();
+        let both = left.append(&right);
+        assert_eq!(both, (1..=5).collect());
+        assert_eq!(both.len(), 5);
+        assert!(shares(&both.drop(2), &right));
+        assert!(!shares(&both, &left));
+        assert!(shares(&left.append(&List::new()), &left));
+        assert!(shares(&List::new().append(&right), &right));
+    }
+
+    #[test]
+    fn filter_shares_suffix() {
+        let list: List<i32> = [1, 2, 3, 4, 6, 8].into_iter().collect();
+        let even = list.filter(|x| x % 2 == 0);
+        assert_eq!(even, List::from_iter([2, 4, 6, 8]));
+        assert_eq!(even.len(), 4);
+        // Everything from the 4 on passes, so that part isn't copied.
+        assert!(shares(&even.drop(1), &list.drop(3)));
+
+        assert!(shares(&list.filter(|_| true), &list));
+        assert!(list.filter(|_| false).is_empty());
+        assert_eq!(list.filter(|x| *x < 3), List::from_iter([1, 2]));
+    }
+
+    #[test]
+    fn long_chain_drop() {
+        let list: List<u32> = (0..1_000_000).collect();
+        let tail = list.drop(10);
+        drop(list);
+        assert_eq!(tail.len(), 999_990);
+    }
+
+    #[test]
+    fn shared_between_threads() {
+        use std::thread;
+
+        const LEN: usize = 100_000;
+        let history: List<usize> = (0..LEN).collect();
+
+        let handles: Vec<_> = (0..8)
+            .map(|t| {
+                let history = history.clone();
+                thread::spawn(move || {
+                    let mut mine = history.drop(t * 1000);
+                    for i in 0..1000 {
+                        mine = mine.prepend(i).tail().prepend(i);
+                    }
+                    assert_eq!(mine.len(), LEN - t * 1000 + 1000);
+                    let expected: usize = (t * 1000..LEN).chain(0..1000).sum();
+                    assert_eq!(mine.iter().sum::<usize>(), expected);
+                    assert!(shares(&mine.drop(1000), &history.drop(t * 1000)));
+                    mine
+                })
+            })
+            .collect();
+        drop(history);
+
+        // The lists all end in the same long chain, and are dropped at about the same time.
+        let lists: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+        let droppers: Vec<_> = lists
+            .into_iter()
+            .map(|list| thread::spawn(move || drop(list)))
+            .collect();
+        for dropper in droppers {
+            dropper.join().unwrap();
+        }
+    }
+}